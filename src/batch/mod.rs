@@ -0,0 +1,330 @@
+use crate::budget::{self, BudgetFormat};
+use crate::config::Config;
+use crate::models::Submission;
+use crate::render::{render_pdf, RenderOptions};
+use axum::body::Body;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Semaphore};
+use tokio_stream::wrappers::ReceiverStream;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+// Bulk rendering: many submissions in, one ZIP of PDFs out. Proposals are
+// rendered with bounded parallelism and added to the archive in completion
+// order while the archive is streamed to the client.
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct BatchOptions {
+    // Render discarded submissions too
+    #[serde(default)]
+    pub force: bool,
+    // Add each proposal's budget export next to its PDF
+    pub budget: Option<BudgetFormat>,
+}
+
+// One entry of the request body, either a parsed submission or the reason
+// it couldn't be parsed.
+pub struct BatchItem {
+    pub index: usize,
+    pub submission: Result<Submission, String>,
+    // The item as received, when it was valid JSON
+    pub raw: Option<serde_json::Value>,
+}
+
+fn parse_item(index: usize, raw: Result<serde_json::Value, serde_json::Error>) -> BatchItem {
+    match raw {
+        Ok(raw) => BatchItem {
+            index,
+            submission: Submission::deserialize(&raw).map_err(|e| e.to_string()),
+            raw: Some(raw),
+        },
+        Err(e) => BatchItem {
+            index,
+            submission: Err(e.to_string()),
+            raw: None,
+        },
+    }
+}
+
+#[derive(Serialize)]
+struct RenderedEntry {
+    unique_id: String,
+    file: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    budget: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct FailedEntry {
+    index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unique_id: Option<String>,
+    error: String,
+}
+
+#[derive(Serialize)]
+struct Manifest {
+    generated_at: String,
+    total: usize,
+    rendered: Vec<RenderedEntry>,
+    failed: Vec<FailedEntry>,
+}
+
+// Accepts a JSON array of submissions or NDJSON (one submission per line,
+// like requests.jsonl). A malformed entry fails on its own without
+// rejecting the rest of the batch.
+pub fn parse_submissions(body: &[u8], ndjson: bool) -> Result<Vec<BatchItem>, String> {
+    let text = std::str::from_utf8(body).map_err(|e| format!("Body is not UTF-8: {}", e))?;
+
+    if !ndjson && text.trim_start().starts_with('[') {
+        let values: Vec<serde_json::Value> =
+            serde_json::from_str(text).map_err(|e| format!("Invalid JSON array: {}", e))?;
+        return Ok(values
+            .into_iter()
+            .enumerate()
+            .map(|(index, value)| parse_item(index, Ok(value)))
+            .collect());
+    }
+
+    Ok(text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(index, line)| parse_item(index, serde_json::from_str(line)))
+        .collect())
+}
+
+// Names the archive entries after the unique_id, keeping it a plain file
+// name and making repeated ids distinct. Returns the name without extension.
+fn entry_name(unique_id: &str, used: &mut HashSet<String>) -> String {
+    let base: String = unique_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let base = if base.is_empty() { "proposal".to_string() } else { base };
+
+    let mut name = base.clone();
+    let mut n = 2;
+    while !used.insert(name.clone()) {
+        name = format!("{}-{}", base, n);
+        n += 1;
+    }
+    name
+}
+
+// Buffers zip output and forwards it to the response body in chunks.
+struct ChannelWriter {
+    tx: mpsc::Sender<io::Result<Vec<u8>>>,
+    buffer: Vec<u8>,
+}
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= CHUNK_SIZE {
+            self.flush()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let chunk = std::mem::take(&mut self.buffer);
+        self.tx
+            .blocking_send(Ok(chunk))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "client disconnected"))
+    }
+}
+
+pub enum Outcome {
+    Rendered {
+        unique_id: String,
+        pdf: Vec<u8>,
+        budget: Option<(BudgetFormat, Vec<u8>)>,
+    },
+    Failed(FailedEntry),
+}
+
+impl Outcome {
+    pub fn is_rendered(&self) -> bool {
+        matches!(self, Outcome::Rendered { .. })
+    }
+}
+
+// Drains render outcomes into a ZIP written to `writer`, finishing with
+// manifest.json. `on_outcome` is called once per submission for progress.
+pub fn write_zip<W: Write>(
+    writer: W,
+    total: usize,
+    mut outcomes: mpsc::Receiver<Outcome>,
+    mut on_outcome: impl FnMut(&Outcome),
+) -> zip::result::ZipResult<W> {
+    let mut zip = ZipWriter::new_stream(writer);
+    // PDFs are already compressed
+    let stored = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+
+    let mut used = HashSet::new();
+    let mut manifest = Manifest {
+        generated_at: Utc::now().to_rfc3339(),
+        total,
+        rendered: Vec::new(),
+        failed: Vec::new(),
+    };
+
+    while let Some(outcome) = outcomes.blocking_recv() {
+        on_outcome(&outcome);
+        match outcome {
+            Outcome::Rendered { unique_id, pdf, budget } => {
+                let name = entry_name(&unique_id, &mut used);
+                let file = format!("{}.pdf", name);
+                zip.start_file(file.as_str(), stored)?;
+                zip.write_all(&pdf)?;
+                let budget = match budget {
+                    Some((format, content)) => {
+                        let budget_file = format!("{}_budget.{}", name, format.extension());
+                        zip.start_file(budget_file.as_str(), SimpleFileOptions::default())?;
+                        zip.write_all(&content)?;
+                        Some(budget_file)
+                    }
+                    None => None,
+                };
+                manifest.rendered.push(RenderedEntry { unique_id, file, budget });
+            }
+            Outcome::Failed(failed) => manifest.failed.push(failed),
+        }
+    }
+
+    manifest.failed.sort_by_key(|f| f.index);
+    zip.start_file("manifest.json", SimpleFileOptions::default())?;
+    zip.write_all(&serde_json::to_vec_pretty(&manifest).unwrap_or_default())?;
+
+    let mut writer = zip.finish()?.into_inner();
+    writer.flush()?;
+    Ok(writer)
+}
+
+// Starts rendering every item with at most `batch_concurrency` conversions
+// in flight. Failures (unparseable entries, discarded submissions without
+// `force`, render errors) come through the channel as `Outcome::Failed`.
+pub fn spawn_renders(
+    items: Vec<BatchItem>,
+    config: Arc<Config>,
+    options: RenderOptions,
+    batch_options: BatchOptions,
+) -> mpsc::Receiver<Outcome> {
+    let (outcome_tx, outcome_rx) = mpsc::channel(config.batch_concurrency.max(1));
+
+    let semaphore = Arc::new(Semaphore::new(config.batch_concurrency.max(1)));
+    let options = Arc::new(options);
+    tokio::spawn(async move {
+        for item in items {
+            let outcome_tx = outcome_tx.clone();
+
+            let submission = match item.submission {
+                Ok(submission) => submission,
+                Err(error) => {
+                    let _ = outcome_tx
+                        .send(Outcome::Failed(FailedEntry {
+                            index: item.index,
+                            unique_id: None,
+                            error,
+                        }))
+                        .await;
+                    continue;
+                }
+            };
+
+            if submission.is_discarded() && !batch_options.force {
+                let _ = outcome_tx
+                    .send(Outcome::Failed(FailedEntry {
+                        index: item.index,
+                        unique_id: Some(submission.unique_id),
+                        error: "Submission has been discarded".to_string(),
+                    }))
+                    .await;
+                continue;
+            }
+
+            let Ok(permit) = semaphore.clone().acquire_owned().await else {
+                break;
+            };
+            let config = config.clone();
+            let options = options.clone();
+            tokio::spawn(async move {
+                let index = item.index;
+                let result = tokio::task::spawn_blocking(move || {
+                    let rendered = render_pdf(&submission, &config, &options).map(|pdf| {
+                        // a budget that can't be exported leaves the PDF alone
+                        let budget = batch_options.budget.and_then(|format| {
+                            match budget::export(&submission, format) {
+                                Ok(content) => Some((format, content)),
+                                Err(e) => {
+                                    println!("Error exporting budget of {}: {}", submission.unique_id, e);
+                                    None
+                                }
+                            }
+                        });
+                        (pdf, budget)
+                    });
+                    (submission.unique_id, rendered)
+                })
+                .await;
+                drop(permit);
+
+                let outcome = match result {
+                    Ok((unique_id, Ok((pdf, budget)))) => Outcome::Rendered { unique_id, pdf, budget },
+                    Ok((unique_id, Err(e))) => {
+                        println!("Error rendering {}: {}", unique_id, e);
+                        Outcome::Failed(FailedEntry {
+                            index,
+                            unique_id: Some(unique_id),
+                            error: e.message().to_string(),
+                        })
+                    }
+                    Err(e) => Outcome::Failed(FailedEntry {
+                        index,
+                        unique_id: None,
+                        error: format!("Render task failed: {}", e),
+                    }),
+                };
+                let _ = outcome_tx.send(outcome).await;
+            });
+        }
+    });
+
+    outcome_rx
+}
+
+// Renders every item and returns a body that streams the ZIP as proposals
+// finish.
+pub fn render_zip(
+    items: Vec<BatchItem>,
+    config: Arc<Config>,
+    options: RenderOptions,
+    batch_options: BatchOptions,
+) -> Body {
+    let total = items.len();
+    let (body_tx, body_rx) = mpsc::channel(8);
+    let outcomes = spawn_renders(items, config, options, batch_options);
+
+    tokio::task::spawn_blocking(move || {
+        let writer = ChannelWriter {
+            tx: body_tx.clone(),
+            buffer: Vec::new(),
+        };
+        if let Err(e) = write_zip(writer, total, outcomes, |_| {}) {
+            println!("Error writing batch ZIP: {}", e);
+            let _ = body_tx.blocking_send(Err(io::Error::other(e.to_string())));
+        }
+    });
+
+    Body::from_stream(ReceiverStream::new(body_rx))
+}
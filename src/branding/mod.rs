@@ -0,0 +1,103 @@
+use crate::document::{ScriptFonts, StyleDef, StyleName, StyleSheet};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+// Branding profiles: the house style sheet restyled for a department or
+// partner body. Each profile is a `<name>.json` file in BRANDING_DIR whose
+// "styles" object overrides the named styles it lists, e.g.
+//
+//   {"styles": {"Heading1": {"font": "Georgia", "color": "#1F3864"}},
+//    "scriptFonts": {"complex": "Nirmala UI"}}
+//
+// Fields left out keep the house style. Profiles are read once at startup;
+// the built-in house style is always available as "default" (a
+// `default.json` adjusts it).
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+struct ProfileFile {
+    #[serde(default)]
+    styles: BTreeMap<StyleName, StyleDef>,
+    #[serde(default)]
+    script_fonts: ScriptFonts,
+}
+
+#[derive(Debug, Clone)]
+pub struct Branding {
+    // Profile used when a request doesn't name one
+    default_profile: String,
+    profiles: BTreeMap<String, StyleSheet>,
+}
+
+impl Default for Branding {
+    fn default() -> Self {
+        Self {
+            default_profile: DEFAULT_PROFILE.to_string(),
+            profiles: BTreeMap::from([(DEFAULT_PROFILE.to_string(), StyleSheet::default())]),
+        }
+    }
+}
+
+fn load_profile(path: &Path) -> Result<StyleSheet, String> {
+    let data = fs::read(path).map_err(|e| e.to_string())?;
+    let profile: ProfileFile = serde_json::from_slice(&data).map_err(|e| e.to_string())?;
+    Ok(StyleSheet::with_overrides(&profile.styles, &profile.script_fonts))
+}
+
+impl Branding {
+    // Reads every profile in `dir`. Files that fail to parse are skipped
+    // with a message so one bad profile doesn't take the others down.
+    pub fn load(dir: &str, default_profile: &str) -> Self {
+        let mut branding = Self::default();
+
+        match fs::read_dir(dir) {
+            Ok(entries) => {
+                for path in entries.flatten().map(|entry| entry.path()) {
+                    if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                        continue;
+                    }
+                    let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                        continue;
+                    };
+                    match load_profile(&path) {
+                        Ok(sheet) => {
+                            println!("Loaded branding profile: {}", name);
+                            branding.profiles.insert(name.to_string(), sheet);
+                        }
+                        Err(e) => println!("Ignoring branding profile {}: {}", path.display(), e),
+                    }
+                }
+            }
+            // no directory just means no custom profiles
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => println!("Failed to read branding directory {}: {}", dir, e),
+        }
+
+        if branding.profiles.contains_key(default_profile) {
+            branding.default_profile = default_profile.to_string();
+        } else {
+            println!("Unknown BRANDING_PROFILE {}, using {}", default_profile, DEFAULT_PROFILE);
+        }
+        branding
+    }
+
+    pub fn from_env() -> Self {
+        let dir = env::var("BRANDING_DIR").unwrap_or_else(|_| "branding".to_string());
+        let profile = env::var("BRANDING_PROFILE")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+        Self::load(&dir, profile.trim())
+    }
+
+    // Style sheet of `profile`, or of the default profile when none is
+    // given; None for an unknown profile
+    pub fn style_sheet(&self, profile: Option<&str>) -> Option<&StyleSheet> {
+        self.profiles.get(profile.unwrap_or(&self.default_profile))
+    }
+}
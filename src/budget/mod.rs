@@ -0,0 +1,156 @@
+use crate::models::Submission;
+use crate::sheet::{self, cell_ref, Sheet, Value};
+use serde::Deserialize;
+
+// Budget export for the finance office: every budget item of a proposal
+// with its per-year amounts, total and justification, one category after
+// another. Each category ends with a subtotal row and the table with a
+// grand total. Item totals, subtotals and the grand total are SUM formulas
+// in XLSX, so the sheet stays correct when finance edits an amount; the
+// total the applicant stated is kept beside it for comparison.
+
+// `?format=` of the budget export, case-insensitive
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum BudgetFormat {
+    Csv,
+    Xlsx,
+}
+
+impl BudgetFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            BudgetFormat::Csv => "csv",
+            BudgetFormat::Xlsx => "xlsx",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            BudgetFormat::Csv => "text/csv; charset=utf-8",
+            BudgetFormat::Xlsx => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        }
+    }
+}
+
+impl TryFrom<String> for BudgetFormat {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.to_ascii_lowercase().as_str() {
+            "csv" => Ok(BudgetFormat::Csv),
+            "xlsx" => Ok(BudgetFormat::Xlsx),
+            _ => Err(format!("Unsupported format \"{}\"; expected xlsx or csv", value)),
+        }
+    }
+}
+
+pub fn budget_filename(submission: &Submission, format: BudgetFormat) -> String {
+    format!("{}_budget.{}", submission.unique_id, format.extension())
+}
+
+// Three year columns as on the proposal, more if an item runs longer
+fn year_count(submission: &Submission) -> usize {
+    submission
+        .budget
+        .iter()
+        .flatten()
+        .flat_map(|category| &category.items)
+        .map(|item| item.years.len())
+        .max()
+        .unwrap_or(0)
+        .max(3)
+}
+
+fn sum(column: usize, rows: &[usize]) -> String {
+    let cells: Vec<String> = rows.iter().map(|&row| cell_ref(row, column)).collect();
+    format!("={}", cells.join("+"))
+}
+
+pub fn budget_sheet(submission: &Submission) -> Sheet {
+    let years = year_count(submission);
+    let year_titles: Vec<String> = (1..=years).map(|year| format!("Year {}", year)).collect();
+    let mut header = vec!["Category", "Item"];
+    header.extend(year_titles.iter().map(String::as_str));
+    header.extend(["Total", "Stated total", "Justification"]);
+    let mut table = Sheet::new("Budget", &header);
+
+    // columns 2.. hold the years, then the total and the stated total
+    let amount_columns = 2..years + 4;
+    let (total_column, stated_column) = (years + 2, years + 3);
+    let mut subtotal_rows = Vec::new();
+    let mut grand_total = vec![0i64; years + 2];
+
+    for category in submission.budget.iter().flatten() {
+        // sheet rows, counting the header as row 0
+        let first = table.rows.len() + 1;
+        let mut totals = vec![0i64; years + 2];
+        for item in &category.items {
+            let row_number = table.rows.len() + 1;
+            let mut row = vec![
+                Value::from(category.category_type.as_str()),
+                Value::from(item.heading.as_str()),
+            ];
+            let mut item_total = 0;
+            for (year, total) in totals.iter_mut().take(years).enumerate() {
+                let amount = item.years.get(year).copied().unwrap_or(0) as i64;
+                *total += amount;
+                item_total += amount;
+                row.push(Value::from(amount));
+            }
+            totals[total_column - 2] += item_total;
+            row.push(Value::Formula {
+                formula: format!("=SUM({}:{})", cell_ref(row_number, 2), cell_ref(row_number, total_column - 1)),
+                result: item_total,
+            });
+            // the total as the applicant stated it, which may not add up
+            totals[stated_column - 2] += item.total as i64;
+            row.push(Value::from(item.total as i64));
+            row.push(Value::from(item.justification.as_str()));
+            table.rows.push(row);
+        }
+        let last = table.rows.len();
+
+        let mut row = vec![
+            Value::from(format!("{} total", category.category_type)),
+            Value::from(""),
+        ];
+        for (column, total) in amount_columns.clone().zip(&totals) {
+            let formula = if last >= first {
+                format!("=SUM({}:{})", cell_ref(first, column), cell_ref(last, column))
+            } else {
+                "=0".to_string()
+            };
+            row.push(Value::Formula { formula, result: *total });
+        }
+        row.push(Value::from(""));
+        table.rows.push(row);
+        subtotal_rows.push(table.rows.len());
+        for (grand, total) in grand_total.iter_mut().zip(&totals) {
+            *grand += total;
+        }
+    }
+
+    let mut row = vec![Value::from("Grand total"), Value::from("")];
+    for (column, total) in amount_columns.zip(&grand_total) {
+        let formula = if subtotal_rows.is_empty() {
+            "=0".to_string()
+        } else {
+            sum(column, &subtotal_rows)
+        };
+        row.push(Value::Formula { formula, result: *total });
+    }
+    row.push(Value::from(""));
+    table.rows.push(row);
+
+    table
+}
+
+// The budget in the requested format
+pub fn export(submission: &Submission, format: BudgetFormat) -> Result<Vec<u8>, String> {
+    let sheets = [budget_sheet(submission)];
+    match format {
+        BudgetFormat::Csv => Ok(sheet::to_csv(&sheets).into_bytes()),
+        BudgetFormat::Xlsx => sheet::to_xlsx(&sheets).map_err(|e| e.to_string()),
+    }
+}
@@ -0,0 +1,160 @@
+use crate::config::{CacheConfig, Config, CoverDateSource};
+use crate::models::Submission;
+use crate::render::{self, RenderOptions};
+use chrono::Local;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
+
+// Content-addressed cache of rendered PDFs. The key hashes everything that
+// affects the output: the normalized submission, the request's render
+// options, the template settings, the branding style sheet, the signing
+// certificate and TEMPLATE_VERSION. The key doubles as
+// the response ETag. Cover thumbnails are kept next to the PDF they were
+// rasterized from, under the same key.
+
+// Bump whenever the document layout changes so stale PDFs aren't served.
+pub const TEMPLATE_VERSION: &str = "5";
+
+pub fn render_key(submission: &Submission, config: &Config, options: &RenderOptions) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(TEMPLATE_VERSION.as_bytes());
+    hasher.update(serde_json::to_vec(submission).unwrap_or_default());
+    hasher.update(serde_json::to_vec(options).unwrap_or_default());
+    hasher.update(format!("{:?}", config.template).as_bytes());
+    // profiles are reloaded on restart, so hash the sheet rather than its name
+    if let Some(styles) = config.branding.style_sheet(options.branding.as_deref()) {
+        hasher.update(serde_json::to_vec(styles).unwrap_or_default());
+    }
+    if let Some(signer) = config.signing.signer.as_ref().filter(|_| render::signing_enabled(config, options)) {
+        hasher.update(signer.fingerprint());
+    }
+    // a cover dated "today" changes every day
+    if config.template.cover_date == CoverDateSource::Generated {
+        hasher.update(Local::now().date_naive().to_string().as_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+// Keeps temporary files of concurrent writes of the same key apart
+static WRITE_SEQ: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Clone)]
+pub struct RenderCache {
+    dir: PathBuf,
+    max_bytes: u64,
+    ttl: Duration,
+}
+
+impl RenderCache {
+    pub fn new(config: &CacheConfig) -> Self {
+        Self {
+            dir: PathBuf::from(&config.dir),
+            max_bytes: config.max_bytes,
+            ttl: Duration::from_secs(config.ttl_secs),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.max_bytes > 0
+    }
+
+    fn pdf_name(key: &str) -> String {
+        format!("{}.pdf", key)
+    }
+
+    pub fn thumbnail_name(key: &str, width: u32, extension: &str) -> String {
+        format!("{}.w{}.{}", key, width, extension)
+    }
+
+    pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+        self.read(&Self::pdf_name(key))
+    }
+
+    pub fn put(&self, key: &str, pdf: &[u8]) {
+        self.write(&Self::pdf_name(key), pdf)
+    }
+
+    // Reads a cached file by name, e.g. from `thumbnail_name`
+    pub fn read(&self, name: &str) -> Option<Vec<u8>> {
+        if !self.enabled() {
+            return None;
+        }
+        let path = self.dir.join(name);
+        let data = fs::read(&path).ok()?;
+        // refresh the modification time so eviction drops the least recently used
+        if let Ok(file) = fs::File::options().append(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        Some(data)
+    }
+
+    pub fn write(&self, name: &str, data: &[u8]) {
+        if !self.enabled() || data.len() as u64 > self.max_bytes {
+            return;
+        }
+        if let Err(e) = fs::create_dir_all(&self.dir) {
+            println!("Error creating cache directory: {}", e);
+            return;
+        }
+
+        // write then rename so readers never see a partial file
+        let seq = WRITE_SEQ.fetch_add(1, Ordering::Relaxed);
+        let tmp = self.dir.join(format!("{}.{}.tmp", name, seq));
+        let written = fs::write(&tmp, data).and_then(|_| fs::rename(&tmp, self.dir.join(name)));
+        if let Err(e) = written {
+            println!("Error caching {}: {}", name, e);
+            let _ = fs::remove_file(&tmp);
+            return;
+        }
+        self.evict();
+    }
+
+    // Removes entries older than the TTL, then the least recently used ones
+    // until the cache fits in `max_bytes`.
+    pub fn evict(&self) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        let now = SystemTime::now();
+
+        let mut files: Vec<(PathBuf, u64, SystemTime)> = entries
+            .flatten()
+            .filter(|entry| {
+                entry
+                    .path()
+                    .extension()
+                    .is_some_and(|ext| ext == "pdf" || ext == "png" || ext == "webp")
+            })
+            .filter_map(|entry| {
+                let metadata = entry.metadata().ok()?;
+                let modified = metadata.modified().ok()?;
+                metadata.is_file().then(|| (entry.path(), metadata.len(), modified))
+            })
+            .collect();
+
+        files.retain(|(path, _, modified)| {
+            let expired = now.duration_since(*modified).is_ok_and(|age| age > self.ttl);
+            if expired {
+                let _ = fs::remove_file(path);
+            }
+            !expired
+        });
+
+        let mut total: u64 = files.iter().map(|(_, len, _)| len).sum();
+        if total <= self.max_bytes {
+            return;
+        }
+        files.sort_by_key(|(_, _, modified)| *modified);
+        for (path, len, _) in files {
+            if total <= self.max_bytes {
+                break;
+            }
+            if fs::remove_file(&path).is_ok() {
+                total -= len;
+            }
+        }
+    }
+}
@@ -0,0 +1,268 @@
+use crate::branding::Branding;
+use crate::evaluation::ScoringCriteria;
+use crate::sanction::LetterTemplate;
+use crate::signing::Signer;
+use chrono::format::{Item, StrftimeItems};
+use std::env;
+
+fn env_flag(name: &str) -> Option<bool> {
+    let value = env::var(name).ok()?;
+    match value.trim().to_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => {
+            println!("Ignoring invalid boolean for {}: {}", name, value);
+            None
+        }
+    }
+}
+
+fn env_positive<T: std::str::FromStr + PartialOrd + Default>(name: &str) -> Option<T> {
+    let value = env::var(name).ok()?;
+    match value.trim().parse::<T>() {
+        Ok(n) if n > T::default() => Some(n),
+        _ => {
+            println!("Ignoring invalid value for {}: {}", name, value);
+            None
+        }
+    }
+}
+
+// Which submission timestamp is printed on the cover page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoverDateSource {
+    Generated,
+    Created,
+    Updated,
+    Submitted,
+}
+
+impl CoverDateSource {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "generated" | "now" => Some(Self::Generated),
+            "created" | "createdat" => Some(Self::Created),
+            "updated" | "updatedat" => Some(Self::Updated),
+            "submitted" | "submittedat" => Some(Self::Submitted),
+            _ => None,
+        }
+    }
+}
+
+// Per-template settings for the SEED proposal document.
+#[derive(Debug, Clone)]
+pub struct TemplateConfig {
+    pub cover_date: CoverDateSource,
+    pub cover_date_format: String,
+    pub show_header: bool,
+    pub show_footer: bool,
+    // Stamp DRAFT across every page of draft submissions
+    pub draft_watermark: bool,
+    // Watermark for everything else, e.g. CONFIDENTIAL
+    pub default_watermark: Option<String>,
+    // Export PDF/A-2b for archiving
+    pub pdfa: bool,
+    // Contents page after the cover
+    pub table_of_contents: bool,
+    // Verification QR code next to the cover logo
+    pub cover_qr: bool,
+    // Turns the QR code into a link, e.g. https://dms.example.edu/verify/
+    pub verify_url_prefix: Option<String>,
+}
+
+impl Default for TemplateConfig {
+    fn default() -> Self {
+        Self {
+            cover_date: CoverDateSource::Submitted,
+            cover_date_format: "%B %Y".to_string(),
+            show_header: true,
+            show_footer: true,
+            draft_watermark: true,
+            default_watermark: None,
+            pdfa: false,
+            table_of_contents: false,
+            cover_qr: false,
+            verify_url_prefix: None,
+        }
+    }
+}
+
+impl TemplateConfig {
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+
+        if let Ok(value) = env::var("COVER_DATE_SOURCE") {
+            match CoverDateSource::parse(&value) {
+                Some(source) => config.cover_date = source,
+                None => println!("Ignoring unknown COVER_DATE_SOURCE: {}", value),
+            }
+        }
+        if let Ok(value) = env::var("COVER_DATE_FORMAT") {
+            // chrono panics while formatting with an invalid specifier
+            if StrftimeItems::new(&value).any(|item| item == Item::Error) {
+                println!("Ignoring invalid COVER_DATE_FORMAT: {}", value);
+            } else {
+                config.cover_date_format = value;
+            }
+        }
+        if let Some(value) = env_flag("PAGE_HEADER") {
+            config.show_header = value;
+        }
+        if let Some(value) = env_flag("PAGE_FOOTER") {
+            config.show_footer = value;
+        }
+        if let Some(value) = env_flag("DRAFT_WATERMARK") {
+            config.draft_watermark = value;
+        }
+        if let Ok(value) = env::var("WATERMARK_TEXT") {
+            if !value.trim().is_empty() {
+                config.default_watermark = Some(value.trim().to_uppercase());
+            }
+        }
+        if let Some(value) = env_flag("PDF_A") {
+            config.pdfa = value;
+        }
+        if let Some(value) = env_flag("TABLE_OF_CONTENTS") {
+            config.table_of_contents = value;
+        }
+        if let Some(value) = env_flag("COVER_QR") {
+            config.cover_qr = value;
+        }
+        if let Ok(value) = env::var("VERIFY_URL_PREFIX") {
+            if !value.trim().is_empty() {
+                config.verify_url_prefix = Some(value.trim().to_string());
+            }
+        }
+
+        config
+    }
+}
+
+// Connection to the DMS backend. Without DMS_BASE_URL the endpoints that
+// fetch submissions from the DMS are unavailable.
+#[derive(Debug, Clone)]
+pub struct DmsConfig {
+    pub base_url: Option<String>,
+    pub email: String,
+    pub password: String,
+    pub login_path: String,
+    pub refresh_path: String,
+    pub submissions_path: String,
+    pub timeout_secs: u64,
+}
+
+impl DmsConfig {
+    pub fn from_env() -> Self {
+        let var = |name: &str, default: &str| env::var(name).unwrap_or_else(|_| default.to_string());
+
+        Self {
+            base_url: env::var("DMS_BASE_URL").ok().filter(|v| !v.trim().is_empty()),
+            email: var("DMS_EMAIL", ""),
+            password: var("DMS_PASSWORD", ""),
+            login_path: var("DMS_LOGIN_PATH", "/auth/login"),
+            refresh_path: var("DMS_REFRESH_PATH", "/auth/refresh"),
+            submissions_path: var("DMS_SUBMISSIONS_PATH", "/submissions"),
+            timeout_secs: env_positive("DMS_TIMEOUT_SECS").unwrap_or(30),
+        }
+    }
+}
+
+// Rendered PDFs kept by content hash. A `max_bytes` of 0 disables the cache.
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    pub dir: String,
+    pub max_bytes: u64,
+    pub ttl_secs: u64,
+}
+
+impl CacheConfig {
+    pub fn from_env() -> Self {
+        let max_mb: u64 = env::var("RENDER_CACHE_MAX_MB")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(512);
+
+        Self {
+            dir: env::var("RENDER_CACHE_DIR").unwrap_or_else(|_| "data/cache".to_string()),
+            max_bytes: max_mb * 1024 * 1024,
+            ttl_secs: env_positive("RENDER_CACHE_TTL_SECS").unwrap_or(7 * 24 * 60 * 60),
+        }
+    }
+}
+
+// PAdES signing of rendered PDFs with a PKCS#12 certificate. Without a
+// certificate PDFs are left unsigned and /verify is unavailable.
+#[derive(Debug, Clone)]
+pub struct SigningConfig {
+    pub signer: Option<Signer>,
+    // Sign every PDF unless the request passes sign=false
+    pub sign_by_default: bool,
+}
+
+impl SigningConfig {
+    pub fn from_env() -> Self {
+        let signer = env::var("SIGNING_CERT").ok().filter(|v| !v.trim().is_empty()).and_then(|path| {
+            let password = env::var("SIGNING_PASSWORD").unwrap_or_default();
+            let reason = env::var("SIGNING_REASON")
+                .unwrap_or_else(|_| "Generated by the COE-DSAI document management system".to_string());
+            match Signer::load(path.trim(), &password, &reason) {
+                Ok(signer) => {
+                    println!("Signing PDFs as {}", signer.name);
+                    Some(signer)
+                }
+                Err(e) => {
+                    println!("Failed to load signing certificate {}", e);
+                    None
+                }
+            }
+        });
+
+        Self {
+            sign_by_default: env_flag("SIGN_PDF").unwrap_or(signer.is_some()),
+            signer,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub template: TemplateConfig,
+    pub dms: DmsConfig,
+    // Number of LibreOffice conversions a batch runs at once
+    pub batch_concurrency: usize,
+    // Number of background jobs running at once; the rest wait queued
+    pub job_concurrency: usize,
+    // How long finished jobs and files in output/ are kept
+    pub job_ttl_secs: i64,
+    // Directory of the embedded submission store
+    pub store_path: String,
+    pub cache: CacheConfig,
+    // Style sheets selectable per request
+    pub branding: Branding,
+    // Font files made available to LibreOffice
+    pub fonts_dir: String,
+    pub signing: SigningConfig,
+    // Criteria of the reviewer evaluation form
+    pub evaluation: ScoringCriteria,
+    // Wording of sanction letters
+    pub sanction: LetterTemplate,
+}
+
+impl Config {
+    pub fn from_env() -> Self {
+        Self {
+            template: TemplateConfig::from_env(),
+            dms: DmsConfig::from_env(),
+            batch_concurrency: env_positive("BATCH_CONCURRENCY").unwrap_or(2),
+            job_concurrency: env_positive("JOB_CONCURRENCY").unwrap_or(1),
+            job_ttl_secs: env_positive("JOB_TTL_SECS").unwrap_or(24 * 60 * 60),
+            store_path: env::var("STORE_PATH").unwrap_or_else(|_| "data/store".to_string()),
+            cache: CacheConfig::from_env(),
+            branding: Branding::from_env(),
+            fonts_dir: env::var("FONTS_DIR").unwrap_or_else(|_| "fonts".to_string()),
+            signing: SigningConfig::from_env(),
+            evaluation: ScoringCriteria::from_env(),
+            sanction: LetterTemplate::from_env(),
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use crate::config::TemplateConfig;
+use crate::models::{Submission, SubmissionStatus};
+use chrono::{DateTime, Local};
+use crate::document::docx::latin_fonts;
+use docx_rs::{AlignmentType, FieldCharType, Footer, Header, InstrText, Paragraph, Run, XMLDocx};
+
+// Running headers/footers and diagonal watermarks. The cover page keeps an
+// empty first-page header/footer so only the watermark shows there.
+
+fn small_run(text: &str) -> Run {
+    Run::new()
+        .add_text(text)
+        .size(18)
+        .fonts(latin_fonts("Calibri"))
+        .color("#666666")
+}
+
+// A complex field such as PAGE or NUMPAGES; LibreOffice recalculates the
+// cached "1" on export.
+fn field_runs(paragraph: Paragraph, instr: &str) -> Paragraph {
+    paragraph
+        .add_run(Run::new().add_field_char(FieldCharType::Begin, true))
+        .add_run(Run::new().add_instr_text(InstrText::Unsupported(instr.to_string())))
+        .add_run(Run::new().add_field_char(FieldCharType::Separate, false))
+        .add_run(small_run("1"))
+        .add_run(Run::new().add_field_char(FieldCharType::End, false))
+}
+
+pub fn page_header(submission: &Submission) -> Header {
+    let title = submission.project_title.as_deref().unwrap_or("");
+    let text = if title.is_empty() {
+        submission.unique_id.clone()
+    } else {
+        format!("{}  |  {}", submission.unique_id, title)
+    };
+
+    Header::new().add_paragraph(
+        Paragraph::new()
+            .align(AlignmentType::Right)
+            .add_run(small_run(&text)),
+    )
+}
+
+pub fn page_footer(generated_at: DateTime<Local>) -> Footer {
+    let mut page_of = Paragraph::new()
+        .align(AlignmentType::Center)
+        .add_run(small_run("Page "));
+    page_of = field_runs(page_of, "PAGE");
+    page_of = page_of.add_run(small_run(" of "));
+    page_of = field_runs(page_of, "NUMPAGES");
+
+    Footer::new().add_paragraph(page_of).add_paragraph(
+        Paragraph::new().align(AlignmentType::Center).add_run(small_run(&format!(
+            "Generated on {}",
+            generated_at.format("%d %B %Y, %H:%M")
+        ))),
+    )
+}
+
+// Works out the watermark text. An explicit request option wins ("none"
+// turns it off), then drafts get DRAFT, then the template-wide default.
+pub fn resolve_watermark(
+    submission: &Submission,
+    template: &TemplateConfig,
+    requested: Option<&str>,
+) -> Option<String> {
+    if let Some(requested) = requested {
+        let requested = requested.trim();
+        if requested.is_empty() || requested.eq_ignore_ascii_case("none") {
+            return None;
+        }
+        return Some(requested.to_uppercase());
+    }
+
+    if template.draft_watermark && submission.status == SubmissionStatus::Draft {
+        return Some("DRAFT".to_string());
+    }
+
+    template.default_watermark.clone()
+}
+
+fn escape_attr(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+// Word's own "PowerPlusWaterMarkObject" VML text shape, which LibreOffice
+// imports as a diagonal font-work watermark behind the page content.
+fn watermark_xml(text: &str, index: usize) -> String {
+    format!(
+        concat!(
+            r#"<w:p><w:r><w:pict>"#,
+            r#"<v:shapetype id="_x0000_t136" coordsize="21600,21600" o:spt="136" adj="10800" path="m@7,l@8,m@5,21600l@6,21600e">"#,
+            r#"<v:formulas><v:f eqn="sum #0 0 10800"/><v:f eqn="prod #0 2 1"/><v:f eqn="sum 21600 0 @1"/><v:f eqn="sum 0 0 @2"/><v:f eqn="sum 21600 0 @3"/><v:f eqn="if @0 @3 0"/><v:f eqn="if @0 21600 @1"/><v:f eqn="if @0 0 @2"/><v:f eqn="if @0 @4 21600"/><v:f eqn="mid @5 @6"/><v:f eqn="mid @8 @5"/><v:f eqn="mid @7 @8"/><v:f eqn="mid @6 @7"/><v:f eqn="sum @6 0 @5"/></v:formulas>"#,
+            r#"<v:path textpathok="t" o:connecttype="custom" o:connectlocs="@9,0;@10,10800;@11,21600;@12,10800" o:connectangles="270,180,90,0"/>"#,
+            r#"<v:textpath on="t" fitshape="t"/><o:lock v:ext="edit" text="t" shapetype="t"/></v:shapetype>"#,
+            r##"<v:shape id="PowerPlusWaterMarkObject{index}" o:spid="_x0000_s{spid}" type="#_x0000_t136" "##,
+            r#"style="position:absolute;margin-left:0;margin-top:0;width:468pt;height:117pt;rotation:315;z-index:-251654144;mso-position-horizontal:center;mso-position-horizontal-relative:margin;mso-position-vertical:center;mso-position-vertical-relative:margin" "#,
+            r#"o:allowincell="f" fillcolor="silver" stroked="f"><v:fill opacity=".5"/>"#,
+            r#"<v:textpath style="font-family:&quot;Calibri&quot;;font-size:1pt" string="{text}"/></v:shape>"#,
+            r#"</w:pict></w:r></w:p>"#
+        ),
+        index = index,
+        spid = 2049 + index,
+        text = escape_attr(text),
+    )
+}
+
+// Inserts the watermark shape into every header part of the built document.
+pub fn apply_watermark(xml: &mut XMLDocx, text: &str) {
+    for (index, header) in xml.headers.iter_mut().enumerate() {
+        let source = String::from_utf8_lossy(header).into_owned();
+        if let Some(pos) = source.rfind("</w:hdr>") {
+            let mut patched = source[..pos].to_string();
+            patched.push_str(&watermark_xml(text, index + 1));
+            patched.push_str(&source[pos..]);
+            *header = patched.into_bytes();
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use crate::document::{Block, Cell, Document, Paragraph, Section, Span, StyleName, StyleSheet, Table, TextStyle};
+use crate::models::{BudgetItem, Submission};
+use crate::page2::{section_a_fields, section_b_fields};
+use chrono::{DateTime, Local, Utc};
+use similar::{ChangeTag, TextDiff};
+
+// Redline report between two stored versions of a submission: every field
+// of the proposal template that changed is shown with deleted text struck
+// through in red and inserted text underlined in green, followed by the
+// budget rows that were added, removed or edited. Text is set in the
+// proposal's named styles, so branding applies; only the change marks carry
+// their own colours.
+
+const INSERT_COLOR: &str = "#00703C";
+const DELETE_COLOR: &str = "#C00000";
+
+pub struct DiffSide<'a> {
+    pub version: u32,
+    pub stored_at: DateTime<Utc>,
+    pub submission: &'a Submission,
+}
+
+pub fn diff_filename(unique_id: &str, from: u32, to: u32, extension: &str) -> String {
+    format!("diff_{}_v{}_v{}.{}", unique_id, from, to, extension)
+}
+
+// Field text is FieldValue; table cells keep their TableCell style
+fn text_style() -> TextStyle {
+    TextStyle::new().character(StyleName::FieldValue)
+}
+
+fn cell_style() -> TextStyle {
+    TextStyle::new()
+}
+
+fn body_span(text: &str, style: &TextStyle) -> Span {
+    Span {
+        text: text.to_string(),
+        style: style.clone(),
+    }
+}
+
+fn inserted_span(text: &str, style: &TextStyle) -> Span {
+    Span {
+        text: text.to_string(),
+        style: style.clone().color(INSERT_COLOR).underline(),
+    }
+}
+
+fn deleted_span(text: &str, style: &TextStyle) -> Span {
+    Span {
+        text: text.to_string(),
+        style: style.clone().color(DELETE_COLOR).strike(),
+    }
+}
+
+fn note(text: &str) -> Block {
+    Block::Paragraph(Paragraph::new().text(text, text_style().italic()))
+}
+
+// Word-level diff of two texts as spans in `style`, merging neighbouring
+// words with the same change so the document doesn't get one run per word.
+fn diff_spans(old: &str, new: &str, style: &TextStyle) -> Vec<Span> {
+    let diff = TextDiff::from_words(old, new);
+    let mut spans: Vec<(ChangeTag, String)> = Vec::new();
+    for change in diff.iter_all_changes() {
+        match spans.last_mut() {
+            Some((tag, text)) if *tag == change.tag() => text.push_str(change.value()),
+            _ => spans.push((change.tag(), change.value().to_string())),
+        }
+    }
+
+    spans
+        .into_iter()
+        .map(|(tag, text)| match tag {
+            ChangeTag::Equal => body_span(&text, style),
+            ChangeTag::Insert => inserted_span(&text, style),
+            ChangeTag::Delete => deleted_span(&text, style),
+        })
+        .collect()
+}
+
+fn diff_paragraph(old: &str, new: &str) -> Block {
+    Block::Paragraph(
+        diff_spans(old, new, &text_style())
+            .into_iter()
+            .fold(Paragraph::new(), |paragraph, span| paragraph.span(span)),
+    )
+}
+
+// The changed fields of one template section, and how many fields changed.
+fn diff_section(
+    title: &str,
+    old: Vec<(&'static str, String)>,
+    new: Vec<(&'static str, String)>,
+) -> (Section, usize) {
+    let mut blocks = vec![Block::Blank];
+
+    let mut changed = 0;
+    let mut unchanged = Vec::new();
+    for ((label, old_text), (_, new_text)) in old.into_iter().zip(new) {
+        if old_text == new_text {
+            unchanged.push(label);
+            continue;
+        }
+        changed += 1;
+        blocks.push(Block::Heading(label.to_string()));
+        blocks.push(diff_paragraph(&old_text, &new_text));
+    }
+
+    if changed == 0 {
+        blocks.push(note("No changes in this section."));
+    } else if !unchanged.is_empty() {
+        blocks.push(Block::Field {
+            label: "Unchanged: ".to_string(),
+            value: unchanged.join(", "),
+        });
+    }
+    (Section::titled(title, blocks), changed)
+}
+
+// Budget items keyed by category and item id (the heading when there is
+// no id), in the order they appear.
+fn budget_items(submission: &Submission) -> Vec<(String, String, &BudgetItem)> {
+    submission
+        .budget
+        .iter()
+        .flatten()
+        .flat_map(|category| {
+            category.items.iter().map(move |item| {
+                let key = if item.id.is_empty() { &item.heading } else { &item.id };
+                (category.category_type.clone(), key.clone(), item)
+            })
+        })
+        .collect()
+}
+
+fn item_cells(item: &BudgetItem) -> Vec<String> {
+    let year = |i: usize| item.years.get(i).copied().unwrap_or(0).to_string();
+    vec![
+        item.heading.clone(),
+        year(0),
+        year(1),
+        year(2),
+        item.total.to_string(),
+        item.justification.clone(),
+    ]
+}
+
+fn budget_row(category: &str, change: &str, old: Option<&BudgetItem>, new: Option<&BudgetItem>) -> Vec<Cell> {
+    let old_cells = old.map(item_cells);
+    let new_cells = new.map(item_cells);
+
+    let style = cell_style();
+    let mut cells = vec![Cell::new(category)];
+    for i in 0..6 {
+        let spans = match (&old_cells, &new_cells) {
+            (Some(old), Some(new)) if i == 5 => diff_spans(&old[i], &new[i], &style),
+            (Some(old), Some(new)) if old[i] != new[i] => {
+                vec![deleted_span(&old[i], &style), body_span(" ", &style), inserted_span(&new[i], &style)]
+            }
+            (Some(_), Some(new)) => vec![body_span(&new[i], &style)],
+            (Some(old), None) => vec![deleted_span(&old[i], &style)],
+            (None, Some(new)) => vec![inserted_span(&new[i], &style)],
+            (None, None) => Vec::new(),
+        };
+        cells.push(Cell::styled(spans));
+    }
+    cells.push(Cell::new(change));
+    cells
+}
+
+// The budget comparison, and how many budget rows changed.
+fn diff_budget(old: &Submission, new: &Submission) -> (Section, usize) {
+    let mut blocks = vec![Block::Blank, Block::Heading("15. Budget requirement with justification".to_string())];
+
+    let old_items = budget_items(old);
+    let new_items = budget_items(new);
+
+    let header = ["Category", "Item", "Year 1", "Year 2", "Year 3", "Total", "Justification", "Change"];
+    let header_style = cell_style().bold();
+    let mut rows = vec![header
+        .iter()
+        .map(|text| Cell::styled(vec![body_span(text, &header_style)]))
+        .collect()];
+
+    for (category, key, old_item) in &old_items {
+        let new_item = new_items
+            .iter()
+            .find(|(c, k, _)| c == category && k == key)
+            .map(|(_, _, item)| *item);
+        match new_item {
+            None => rows.push(budget_row(category, "Removed", Some(old_item), None)),
+            Some(new_item) if item_cells(old_item) != item_cells(new_item) => {
+                rows.push(budget_row(category, "Changed", Some(old_item), Some(new_item)))
+            }
+            Some(_) => {}
+        }
+    }
+    for (category, key, new_item) in &new_items {
+        if !old_items.iter().any(|(c, k, _)| c == category && k == key) {
+            rows.push(budget_row(category, "Added", None, Some(new_item)));
+        }
+    }
+
+    let changed = rows.len() - 1;
+    if changed == 0 {
+        blocks.push(note("No changes to the budget."));
+    } else {
+        blocks.push(Block::Table(Table { rows }));
+    }
+    (Section::new(blocks), changed)
+}
+
+pub fn diff_document(unique_id: &str, old: &DiffSide, new: &DiffSide, styles: &StyleSheet) -> Document {
+    let describe = |side: &DiffSide| {
+        format!(
+            "version {} (stored {})",
+            side.version,
+            side.stored_at.with_timezone(&Local).format("%d %B %Y, %H:%M")
+        )
+    };
+
+    let title = format!("Changes to proposal {}", unique_id);
+    let style = text_style();
+    let intro = Section::titled(
+        &title,
+        vec![
+            Block::Field {
+                label: "From: ".to_string(),
+                value: describe(old),
+            },
+            Block::Field {
+                label: "To: ".to_string(),
+                value: describe(new),
+            },
+            Block::Paragraph(
+                Paragraph::new()
+                    .span(inserted_span("Inserted text", &style))
+                    .span(body_span("   ", &style))
+                    .span(deleted_span("Deleted text", &style)),
+            ),
+        ],
+    );
+
+    let (section_a, changed_a) = diff_section(
+        "Section A",
+        section_a_fields(old.submission),
+        section_a_fields(new.submission),
+    );
+    let (section_b, changed_b) = diff_section(
+        "Section B",
+        section_b_fields(old.submission),
+        section_b_fields(new.submission),
+    );
+    let (budget, changed_budget) = diff_budget(old.submission, new.submission);
+    let summary = Section::new(vec![
+        Block::Blank,
+        note(&format!(
+            "{} field(s) and {} budget row(s) changed.",
+            changed_a + changed_b,
+            changed_budget
+        )),
+    ]);
+
+    Document {
+        title,
+        styles: styles.clone(),
+        sections: vec![intro, section_a, section_b, budget, summary],
+        landscape: false,
+    }
+}
@@ -0,0 +1,467 @@
+use crate::config::DmsConfig;
+use crate::models::{LoginRequest, LoginResponse, Submission, SubmissionStatus, TrackCode};
+use crate::utils::percent_encode;
+use chrono::NaiveDate;
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::Deserialize;
+use serde_json::Value;
+use std::fmt;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+// Client for the DMS backend API. Logs in with the configured service
+// account, caches the bearer token and refreshes it (falling back to a fresh
+// login) when a request comes back 401.
+
+#[derive(Debug)]
+pub enum DmsError {
+    NotConfigured,
+    Request(reqwest::Error),
+    Status(StatusCode, String),
+    NotFound,
+    Decode(String),
+}
+
+impl fmt::Display for DmsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DmsError::NotConfigured => write!(f, "DMS_BASE_URL is not configured"),
+            DmsError::Request(e) => write!(f, "DMS request failed: {}", e),
+            DmsError::Status(status, body) => write!(f, "DMS returned {}: {}", status, body),
+            DmsError::NotFound => write!(f, "Submission not found in DMS"),
+            DmsError::Decode(e) => write!(f, "Unexpected DMS response: {}", e),
+        }
+    }
+}
+
+impl From<reqwest::Error> for DmsError {
+    fn from(e: reqwest::Error) -> Self {
+        DmsError::Request(e)
+    }
+}
+
+// Filters for listing submissions, read from the query string. Status,
+// track and user are passed on to the DMS; everything is also applied here
+// since the DMS may ignore filters it doesn't know.
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct SubmissionQuery {
+    pub status: Option<SubmissionStatus>,
+    #[serde(rename = "trackCode")]
+    pub track_code: Option<TrackCode>,
+    pub user: Option<String>,
+    // Inclusive range on the submitted date (updatedAt for unsubmitted ones)
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+const DEFAULT_PAGE_SIZE: usize = 50;
+const MAX_PAGE_SIZE: usize = 500;
+
+impl SubmissionQuery {
+    pub fn matches(&self, submission: &Submission) -> bool {
+        if self.status.as_ref().is_some_and(|status| *status != submission.status) {
+            return false;
+        }
+        if self
+            .track_code
+            .as_ref()
+            .is_some_and(|track| submission.track_code.as_ref() != Some(track))
+        {
+            return false;
+        }
+        if self.user.as_ref().is_some_and(|user| *user != submission.user) {
+            return false;
+        }
+
+        let date = submission.submitted_at.unwrap_or(submission.updated_at).date_naive();
+        if self.from.is_some_and(|from| date < from) || self.to.is_some_and(|to| date > to) {
+            return false;
+        }
+        true
+    }
+
+    pub fn page(&self) -> usize {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+    }
+
+    // Filters and sorts (most recently updated first), then returns the
+    // requested page along with the total number of matches.
+    pub fn apply(&self, submissions: Vec<Submission>) -> (Vec<Submission>, usize) {
+        let mut matching: Vec<Submission> = submissions.into_iter().filter(|s| self.matches(s)).collect();
+        matching.sort_by_key(|s| std::cmp::Reverse(s.updated_at));
+
+        let total = matching.len();
+        // saturating, since `page` comes straight from the query string
+        let page = matching
+            .into_iter()
+            .skip((self.page() - 1).saturating_mul(self.limit()))
+            .take(self.limit())
+            .collect();
+        (page, total)
+    }
+}
+
+pub struct DmsClient {
+    http: reqwest::Client,
+    config: DmsConfig,
+    token: Mutex<Option<String>>,
+}
+
+impl DmsClient {
+    pub fn new(config: DmsConfig) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            http,
+            config,
+            token: Mutex::new(None),
+        }
+    }
+
+    fn url(&self, path: &str) -> Result<String, DmsError> {
+        let base = self.config.base_url.as_deref().ok_or(DmsError::NotConfigured)?;
+        Ok(format!("{}/{}", base.trim_end_matches('/'), path.trim_start_matches('/')))
+    }
+
+    async fn login(&self) -> Result<String, DmsError> {
+        let response = self
+            .http
+            .post(self.url(&self.config.login_path)?)
+            .json(&LoginRequest {
+                email: self.config.email.clone(),
+                password: self.config.password.clone(),
+            })
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            return Err(DmsError::Status(status, response.text().await.unwrap_or_default()));
+        }
+
+        let login: LoginResponse = response
+            .json()
+            .await
+            .map_err(|e| DmsError::Decode(e.to_string()))?;
+        println!("Logged in to DMS as {}", self.config.email);
+        Ok(login.token)
+    }
+
+    async fn refresh(&self, token: &str) -> Result<String, DmsError> {
+        let response = self
+            .http
+            .post(self.url(&self.config.refresh_path)?)
+            .bearer_auth(token)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            return Err(DmsError::Status(status, response.text().await.unwrap_or_default()));
+        }
+
+        let refreshed: LoginResponse = response
+            .json()
+            .await
+            .map_err(|e| DmsError::Decode(e.to_string()))?;
+        Ok(refreshed.token)
+    }
+
+    async fn current_token(&self) -> Result<String, DmsError> {
+        let mut token = self.token.lock().await;
+        if let Some(token) = token.as_ref() {
+            return Ok(token.clone());
+        }
+        let fresh = self.login().await?;
+        *token = Some(fresh.clone());
+        Ok(fresh)
+    }
+
+    // Replaces an expired token, preferring the refresh endpoint and falling
+    // back to logging in again.
+    async fn renew_token(&self, expired: &str) -> Result<String, DmsError> {
+        let mut token = self.token.lock().await;
+        // another request may already have renewed it
+        if let Some(current) = token.as_ref() {
+            if current != expired {
+                return Ok(current.clone());
+            }
+        }
+
+        let fresh = match self.refresh(expired).await {
+            Ok(fresh) => fresh,
+            Err(e) => {
+                println!("DMS token refresh failed, logging in again: {}", e);
+                self.login().await?
+            }
+        };
+        *token = Some(fresh.clone());
+        Ok(fresh)
+    }
+
+    // Sends an authenticated request, retrying once with a renewed token if
+    // the DMS rejects the current one.
+    async fn get_json(
+        &self,
+        path: &str,
+        build: impl Fn(RequestBuilder) -> RequestBuilder,
+    ) -> Result<Value, DmsError> {
+        let url = self.url(path)?;
+        let mut token = self.current_token().await?;
+
+        for attempt in 0..2 {
+            let response = build(self.http.request(Method::GET, &url))
+                .bearer_auth(&token)
+                .send()
+                .await?;
+
+            match response.status() {
+                StatusCode::UNAUTHORIZED if attempt == 0 => {
+                    token = self.renew_token(&token).await?;
+                }
+                StatusCode::NOT_FOUND => return Err(DmsError::NotFound),
+                status if status.is_success() => {
+                    return response.json().await.map_err(|e| DmsError::Decode(e.to_string()));
+                }
+                status => {
+                    return Err(DmsError::Status(status, response.text().await.unwrap_or_default()));
+                }
+            }
+        }
+
+        Err(DmsError::Status(StatusCode::UNAUTHORIZED, "Token rejected after refresh".to_string()))
+    }
+
+    pub async fn fetch_submission(&self, unique_id: &str) -> Result<Submission, DmsError> {
+        Ok(self.fetch_submission_json(unique_id).await?.0)
+    }
+
+    // The submission along with the record exactly as the DMS returned it
+    pub async fn fetch_submission_json(&self, unique_id: &str) -> Result<(Submission, Value), DmsError> {
+        // the id is one path segment: "/", "?" and the like are escaped, and
+        // dot segments, which URLs resolve even when escaped, can't be ids
+        if unique_id.is_empty() || unique_id == "." || unique_id == ".." {
+            return Err(DmsError::NotFound);
+        }
+        let path = format!(
+            "{}/{}",
+            self.config.submissions_path.trim_end_matches('/'),
+            percent_encode(unique_id)
+        );
+        let value = self.get_json(&path, |request| request).await?;
+
+        // the DMS wraps single records in {"submission": ...} or {"data": ...}
+        let value = unwrap_envelope(value, &["submission", "data"]);
+        if value.is_null() {
+            return Err(DmsError::NotFound);
+        }
+        let submission = Submission::deserialize(&value).map_err(|e| DmsError::Decode(e.to_string()))?;
+        Ok((submission, value))
+    }
+
+    pub async fn list_submissions(&self, query: &SubmissionQuery) -> Result<Vec<Submission>, DmsError> {
+        let mut params = Vec::new();
+        if let Some(status) = &query.status {
+            params.push(("status", String::from(status.clone())));
+        }
+        if let Some(track) = &query.track_code {
+            params.push(("trackCode", String::from(track.clone())));
+        }
+        if let Some(user) = &query.user {
+            params.push(("user", user.clone()));
+        }
+
+        let value = self
+            .get_json(&self.config.submissions_path, |request| request.query(&params))
+            .await?;
+        let value = unwrap_envelope(value, &["submissions", "data"]);
+
+        let Value::Array(entries) = value else {
+            return Err(DmsError::Decode("expected a list of submissions".to_string()));
+        };
+
+        // skip records that no longer match our model rather than failing the listing
+        Ok(entries
+            .into_iter()
+            .filter_map(|entry| match serde_json::from_value::<Submission>(entry) {
+                Ok(submission) => Some(submission),
+                Err(e) => {
+                    println!("Skipping unreadable DMS submission: {}", e);
+                    None
+                }
+            })
+            .collect())
+    }
+}
+
+fn unwrap_envelope(value: Value, keys: &[&str]) -> Value {
+    if let Value::Object(map) = &value {
+        for key in keys {
+            if let Some(inner) = map.get(*key) {
+                return inner.clone();
+            }
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::{Path, State};
+    use axum::http::{HeaderMap, Uri};
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use std::sync::{Arc, Mutex as StdMutex};
+
+    // Local stand-in for the DMS: hands out "token-1" on login and
+    // "token-2" on refresh, and only accepts `valid` as bearer token
+    #[derive(Default)]
+    struct MockDms {
+        valid: String,
+        logins: usize,
+        refreshes: usize,
+        paths: Vec<String>,
+    }
+
+    type Mock = Arc<StdMutex<MockDms>>;
+
+    fn bearer(headers: &HeaderMap) -> String {
+        headers
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .trim_start_matches("Bearer ")
+            .to_string()
+    }
+
+    async fn login(State(mock): State<Mock>) -> Json<Value> {
+        mock.lock().unwrap().logins += 1;
+        Json(serde_json::json!({ "token": "token-1" }))
+    }
+
+    async fn refresh(State(mock): State<Mock>, headers: HeaderMap) -> Result<Json<Value>, StatusCode> {
+        let mut mock = mock.lock().unwrap();
+        mock.refreshes += 1;
+        if bearer(&headers) != "token-1" {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+        Ok(Json(serde_json::json!({ "token": "token-2" })))
+    }
+
+    async fn submission(
+        State(mock): State<Mock>,
+        Path(unique_id): Path<String>,
+        uri: Uri,
+        headers: HeaderMap,
+    ) -> Result<Json<Value>, StatusCode> {
+        let mut mock = mock.lock().unwrap();
+        mock.paths.push(uri.path().to_string());
+        if bearer(&headers) != mock.valid {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+        if unique_id == "missing" {
+            return Err(StatusCode::NOT_FOUND);
+        }
+        let mut submission: Value = serde_json::from_str(include_str!("../../mock_submission.json")).unwrap();
+        submission["unique_id"] = Value::String(unique_id);
+        Ok(Json(serde_json::json!({ "submission": submission })))
+    }
+
+    async fn start(valid: &str) -> (DmsClient, Mock) {
+        let mock: Mock = Arc::new(StdMutex::new(MockDms {
+            valid: valid.to_string(),
+            ..MockDms::default()
+        }));
+        let app = Router::new()
+            .route("/auth/login", post(login))
+            .route("/auth/refresh", post(refresh))
+            .route("/submissions/{unique_id}", get(submission))
+            .with_state(mock.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let client = DmsClient::new(DmsConfig {
+            base_url: Some(format!("http://{}", address)),
+            email: "service@example.edu".to_string(),
+            password: "secret".to_string(),
+            login_path: "/auth/login".to_string(),
+            refresh_path: "/auth/refresh".to_string(),
+            submissions_path: "/submissions".to_string(),
+            timeout_secs: 5,
+        });
+        (client, mock)
+    }
+
+    #[test]
+    fn pages_past_the_end_are_empty() {
+        let submission: Submission = serde_json::from_str(include_str!("../../mock_submission.json")).unwrap();
+        let query = SubmissionQuery {
+            page: Some(usize::MAX),
+            limit: Some(MAX_PAGE_SIZE),
+            ..SubmissionQuery::default()
+        };
+
+        let (page, total) = query.apply(vec![submission]);
+        assert!(page.is_empty());
+        assert_eq!(total, 1);
+    }
+
+    #[tokio::test]
+    async fn logs_in_once_and_reuses_the_token() {
+        let (client, mock) = start("token-1").await;
+
+        let submission = client.fetch_submission("DSAI2025-EE-1007").await.unwrap();
+        assert_eq!(submission.unique_id, "DSAI2025-EE-1007");
+        client.fetch_submission("DSAI2025-EE-1008").await.unwrap();
+
+        let mock = mock.lock().unwrap();
+        assert_eq!(mock.logins, 1);
+        assert_eq!(mock.refreshes, 0);
+    }
+
+    #[tokio::test]
+    async fn refreshes_the_token_on_401() {
+        let (client, mock) = start("token-2").await;
+
+        client.fetch_submission("DSAI2025-EE-1007").await.unwrap();
+
+        let mock = mock.lock().unwrap();
+        assert_eq!(mock.logins, 1);
+        assert_eq!(mock.refreshes, 1);
+        // rejected with the login token, accepted with the refreshed one
+        assert_eq!(mock.paths.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn reports_missing_submissions() {
+        let (client, _mock) = start("token-1").await;
+
+        let result = client.fetch_submission("missing").await;
+        assert!(matches!(result, Err(DmsError::NotFound)), "{:?}", result.err());
+    }
+
+    #[tokio::test]
+    async fn escapes_the_unique_id() {
+        let (client, mock) = start("token-1").await;
+
+        let submission = client.fetch_submission("../auth/login?x=1").await.unwrap();
+        assert_eq!(submission.unique_id, "../auth/login?x=1");
+        assert_eq!(mock.lock().unwrap().paths, ["/submissions/..%2Fauth%2Flogin%3Fx%3D1"]);
+
+        for unique_id in ["", ".", ".."] {
+            assert!(matches!(client.fetch_submission(unique_id).await, Err(DmsError::NotFound)));
+        }
+        assert_eq!(mock.lock().unwrap().paths.len(), 1);
+    }
+}
@@ -0,0 +1,376 @@
+use super::{
+    Align, Block, Cell, Document, Image, Paragraph as Para, ScriptFonts, Section, StyleName, StyleSheet, Table as DocTable,
+    TextStyle,
+};
+use docx_rs::{
+    AlignmentType, BookmarkStart, Docx, FieldCharType, Hyperlink, HyperlinkType, InstrPAGEREF, InstrText, LineSpacing,
+    PageOrientationType, Paragraph, ParagraphChild, Pic, Run, RunFonts, Style, StyleType, Tab, TabLeaderType, TabValueType, Table, TableCell,
+    TableCellBorderPosition, TableRow, WidthType,
+};
+
+// DOCX backend: maps each block onto docx-rs. The document's style sheet
+// becomes Word paragraph and character styles, and paragraphs and runs
+// reference those by ID; only a span's own overrides are written inline, so
+// restyling "Field Value" in Word restyles every answer.
+//
+// Word picks a run's font by character range: ascii/hAnsi for Latin text
+// (and symbols such as ≤ or ∑), cs for complex scripts like Devanagari and
+// Gurmukhi, eastAsia for CJK. Styles set the Latin slots; the document
+// defaults set the other two from the sheet's script fonts.
+//
+// Section titles and headings use Word's "heading 1"/"heading 2" styles so
+// LibreOffice exports them as PDF bookmarks. Each carries a bookmark named
+// after its position in `Document::outline`, which the contents page links
+// to with a PAGEREF field for the page number.
+
+// A4 width less docx-rs' default left and right margins, in twips
+const TEXT_WIDTH: usize = 8504;
+
+const EMU_PER_TWIP: usize = 635;
+
+// A4 in twips
+const PAGE_WIDTH: u32 = 11906;
+const PAGE_HEIGHT: u32 = 16838;
+
+fn alignment(align: Align) -> AlignmentType {
+    match align {
+        Align::Left => AlignmentType::Left,
+        Align::Center => AlignmentType::Center,
+    }
+}
+
+// The Latin font slots. Setting only ascii leaves accented letters and
+// symbols in Word's default font.
+pub fn latin_fonts(font: &str) -> RunFonts {
+    RunFonts::new().ascii(font).hi_ansi(font)
+}
+
+fn default_fonts(docx: Docx, fonts: &ScriptFonts) -> Docx {
+    let mut run_fonts = RunFonts::new();
+    if let Some(font) = &fonts.complex {
+        run_fonts = run_fonts.cs(font);
+    }
+    if let Some(font) = &fonts.east_asian {
+        run_fonts = run_fonts.east_asia(font);
+    }
+    docx.default_fonts(run_fonts)
+}
+
+fn add_styles(docx: Docx, styles: &StyleSheet) -> Docx {
+    styles.iter().fold(docx, |docx, (name, def)| {
+        let mut style = if name.is_character() {
+            Style::new(name.id(), StyleType::Character).name(name.display_name())
+        } else {
+            Style::new(name.id(), StyleType::Paragraph)
+                .name(name.display_name())
+                .based_on("Normal")
+        };
+        if let Some(level) = name.outline_level() {
+            style = style.outline_lvl(level - 1);
+        }
+        match def.bold {
+            Some(true) => style = style.bold(),
+            Some(false) => style.run_property = style.run_property.disable_bold(),
+            None => {}
+        }
+        match def.italic {
+            Some(true) => style = style.italic(),
+            Some(false) => style.run_property = style.run_property.disable_italic(),
+            None => {}
+        }
+        if let Some(size) = def.size {
+            style = style.size(size);
+        }
+        if let Some(font) = &def.font {
+            style = style.fonts(latin_fonts(font));
+        }
+        if let Some(color) = &def.color {
+            style = style.color(color);
+        }
+        if !name.is_character() {
+            if let Some(align) = def.align {
+                style = style.align(alignment(align));
+            }
+            if let Some(indent) = def.indent {
+                style = style.indent(Some(indent), None, None, None);
+            }
+        }
+        docx.add_style(style)
+    })
+}
+
+fn bookmark(index: usize) -> String {
+    format!("_Toc{:08}", index + 1)
+}
+
+// Heading paragraph wrapped in the bookmark for outline entry `index`
+fn heading(style: StyleName, text: &str, index: usize) -> Paragraph {
+    let mut heading = Paragraph::new()
+        .style(style.id())
+        // keep the heading styles from adding LibreOffice's heading spacing
+        .line_spacing(LineSpacing::new().before(0).after(0))
+        .add_run(Run::new().add_text(text));
+    heading.children.insert(
+        0,
+        ParagraphChild::BookmarkStart(BookmarkStart::new(index + 1, bookmark(index))),
+    );
+    heading.add_bookmark_end(index + 1)
+}
+
+// Contents line: the heading text linked to its bookmark, a dot leader and
+// the page number
+fn contents_entry(level: usize, text: &str, index: usize) -> Paragraph {
+    let style = if level == 1 { StyleName::TOC1 } else { StyleName::TOC2 };
+    let page = Run::new()
+        .add_field_char(FieldCharType::Begin, false)
+        .add_instr_text(InstrText::PAGEREF(InstrPAGEREF::new(bookmark(index)).hyperlink()))
+        .add_field_char(FieldCharType::Separate, false)
+        .add_field_char(FieldCharType::End, false);
+
+    Paragraph::new()
+        .style(style.id())
+        .line_spacing(LineSpacing::new().after(120))
+        .add_tab(Tab::new().val(TabValueType::Right).leader(TabLeaderType::Dot).pos(TEXT_WIDTH))
+        .add_hyperlink(
+            Hyperlink::new(bookmark(index), HyperlinkType::Anchor)
+                .add_run(Run::new().add_text(text))
+                .add_run(Run::new().add_tab())
+                .add_run(page),
+        )
+}
+
+fn run(text: &str, style: &TextStyle) -> Run {
+    let mut run = Run::new().add_text(text);
+    if let Some(character) = style.character {
+        run = run.style(character.id());
+    }
+    if style.bold {
+        run = run.bold();
+    }
+    if style.italic {
+        run = run.italic();
+    }
+    if style.underline {
+        run = run.underline("single");
+    }
+    if style.strike {
+        run.run_property = run.run_property.strike();
+    }
+    if let Some(size) = style.size {
+        run = run.size(size);
+    }
+    if let Some(font) = &style.font {
+        run = run.fonts(latin_fonts(font));
+    }
+    if let Some(color) = &style.color {
+        run = run.color(color);
+    }
+    run
+}
+
+fn paragraph(para: &Para) -> Paragraph {
+    let mut paragraph = Paragraph::new();
+    if let Some(style) = para.style {
+        paragraph = paragraph.style(style.id());
+    }
+    if let Some(align) = para.align {
+        paragraph = paragraph.align(alignment(align));
+    }
+    if let Some(indent) = para.indent {
+        paragraph = paragraph.indent(Some(indent), None, None, None);
+    }
+    para.spans
+        .iter()
+        .fold(paragraph, |paragraph, span| paragraph.add_run(run(&span.text, &span.style)))
+}
+
+fn value_run(value: &str) -> Run {
+    Run::new().add_text(value).style(StyleName::FieldValue.id())
+}
+
+fn field(label: &str, value: &str) -> Paragraph {
+    Paragraph::new()
+        .add_run(Run::new().add_text(label).style(StyleName::FieldLabel.id()))
+        .add_run(value_run(value))
+}
+
+fn list_item(text: &str) -> Paragraph {
+    Paragraph::new()
+        .style(StyleName::ListItem.id())
+        .add_run(Run::new().add_text(format!("• {}", text)))
+}
+
+fn cell(cell: &Cell) -> TableCell {
+    let content = cell
+        .spans
+        .iter()
+        .filter(|span| !span.text.is_empty())
+        .fold(Paragraph::new().style(StyleName::TableCell.id()), |paragraph, span| {
+            paragraph.add_run(run(&span.text, &span.style))
+        });
+
+    let mut table_cell = TableCell::new();
+    if let Some(width) = cell.width {
+        table_cell = table_cell.width(width, WidthType::Dxa);
+    }
+    if cell.open_left {
+        table_cell = table_cell.clear_border(TableCellBorderPosition::Left);
+    }
+    if cell.open_right {
+        table_cell = table_cell.clear_border(TableCellBorderPosition::Right);
+    }
+    table_cell.add_paragraph(content)
+}
+
+fn picture(image: &Image) -> Pic {
+    let pic = Pic::new(&image.data);
+    match image.height {
+        Some(height) => {
+            let (width_emu, height_emu) = pic.size;
+            let scaled = (height * EMU_PER_TWIP) as u64;
+            let width = width_emu as u64 * scaled / height_emu.max(1) as u64;
+            pic.size(width as u32, scaled as u32)
+        }
+        None => pic,
+    }
+}
+
+// Centered line of images with a gap between them
+fn image_row(images: &[Image]) -> Paragraph {
+    images.iter().enumerate().fold(
+        Paragraph::new().align(AlignmentType::Center),
+        |paragraph, (i, image)| {
+            let paragraph = if i > 0 {
+                paragraph.add_run(Run::new().add_text("      "))
+            } else {
+                paragraph
+            };
+            paragraph.add_run(Run::new().add_image(picture(image)))
+        },
+    )
+}
+
+fn table(table: &DocTable) -> Table {
+    let rows = table
+        .rows
+        .iter()
+        .map(|row| TableRow::new(row.iter().map(cell).collect()))
+        .collect();
+    Table::new(rows).width(100, WidthType::Pct)
+}
+
+// Position in `Document::outline` of the next heading, and the outline
+// itself for the contents page
+struct Outline<'a> {
+    entries: Vec<(usize, &'a str)>,
+    next: usize,
+}
+
+impl Outline<'_> {
+    fn next_index(&mut self) -> usize {
+        self.next += 1;
+        self.next - 1
+    }
+}
+
+fn add_blocks(mut docx: Docx, blocks: &[Block], outline: &mut Outline) -> Docx {
+    for block in blocks {
+        docx = match block {
+            Block::Paragraph(para) => docx.add_paragraph(paragraph(para)),
+            Block::Blank => docx.add_paragraph(Paragraph::new()),
+            Block::Spacer => {
+                // 240 is approximately 12pt spacing
+                docx.add_paragraph(Paragraph::new().line_spacing(LineSpacing::new().after(240)))
+            }
+            Block::Heading(text) => {
+                let index = outline.next_index();
+                docx.add_paragraph(heading(StyleName::Heading2, text, index))
+            }
+            Block::Text(text) => docx.add_paragraph(Paragraph::new().add_run(value_run(text))),
+            Block::Field { label, value } => docx.add_paragraph(field(label, value)),
+            Block::List(items) => items
+                .iter()
+                .fold(docx, |docx, item| docx.add_paragraph(list_item(item))),
+            Block::Table(t) => docx.add_table(table(t)),
+            Block::Image(data) => docx.add_paragraph(
+                Paragraph::new()
+                    .align(AlignmentType::Center)
+                    .add_run(Run::new().add_image(Pic::new(data))),
+            ),
+            Block::ImageRow(images) => docx.add_paragraph(image_row(images)),
+            Block::Contents => outline
+                .entries
+                .iter()
+                .enumerate()
+                .fold(docx, |docx, (index, (level, text))| {
+                    docx.add_paragraph(contents_entry(*level, text, index))
+                }),
+        };
+    }
+    docx
+}
+
+fn add_section(mut docx: Docx, section: &Section, outline: &mut Outline) -> Docx {
+    if section.new_page {
+        docx = docx.add_paragraph(Paragraph::new().page_break_before(true));
+    }
+    if let Some(title) = &section.title {
+        let index = outline.next_index();
+        docx = docx.add_paragraph(heading(StyleName::Heading1, title, index));
+    }
+    add_blocks(docx, &section.blocks, outline)
+}
+
+pub fn to_docx(document: &Document) -> Docx {
+    let mut outline = Outline {
+        entries: document.outline(),
+        next: 0,
+    };
+    let mut docx = add_styles(default_fonts(Docx::new(), &document.styles.script_fonts), &document.styles);
+    if document.landscape {
+        docx = docx
+            .page_size(PAGE_HEIGHT, PAGE_WIDTH)
+            .page_orient(PageOrientationType::Landscape);
+    }
+    document
+        .sections
+        .iter()
+        .fold(docx, |docx, section| add_section(docx, section, &mut outline))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TemplateConfig;
+    use crate::document::proposal;
+    use crate::models::Submission;
+    use std::io::{Cursor, Read};
+
+    fn part(docx: &[u8], name: &str) -> String {
+        let mut archive = zip::ZipArchive::new(Cursor::new(docx)).unwrap();
+        let mut xml = String::new();
+        archive.by_name(name).unwrap().read_to_string(&mut xml).unwrap();
+        xml
+    }
+
+    #[test]
+    fn multiscript_proposal_uses_the_script_fonts() {
+        let submission: Submission =
+            serde_json::from_str(include_str!("../../mock_submission_multiscript.json")).unwrap();
+        let document = proposal(&submission, &TemplateConfig::default(), false, false, &StyleSheet::default());
+        let mut docx = Cursor::new(Vec::new());
+        to_docx(&document).build().pack(&mut docx).unwrap();
+        let docx = docx.into_inner();
+
+        // Devanagari and Gurmukhi fall in the cs slot, CJK in eastAsia
+        let styles = part(&docx, "word/styles.xml");
+        let defaults = &styles[styles.find("<w:docDefaults>").unwrap()..styles.find("</w:docDefaults>").unwrap()];
+        assert!(defaults.contains(r#"w:cs="Noto Sans Devanagari""#), "{}", defaults);
+        assert!(defaults.contains(r#"w:eastAsia="Noto Sans CJK SC""#), "{}", defaults);
+
+        let body = part(&docx, "word/document.xml");
+        assert!(body.contains("स्मार्ट ऊर्जा प्रबंधन"));
+        assert!(body.contains("ਸਮਾਰਟ ਊਰਜਾ ਪ੍ਰਬੰਧਨ"));
+        assert!(body.contains("能源效率"));
+    }
+}
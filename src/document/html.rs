@@ -0,0 +1,358 @@
+use super::{Align, Block, Cell, Document, Image, Paragraph, ScriptFonts, Section, Span, StyleDef, StyleName, StyleSheet, Table};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::fmt::Write;
+
+// HTML backend for in-browser previews. Produces a single self-contained
+// page (inline CSS, images as data URIs) laid out like the A4 document,
+// with page breaks honoured when printing. Each named style becomes a CSS
+// class (`s-Heading1`, ...) generated from the document's style sheet, with
+// the sheet's script fonts as fallbacks for Devanagari, Gurmukhi and CJK.
+// Headings get anchors numbered by their position in `Document::outline`,
+// which the contents page links to.
+
+const STYLE: &str = r#"
+body { background: #e9e9e9; margin: 0; padding: 24px 0; }
+.page { background: #fff; width: 210mm; min-height: 297mm; margin: 0 auto 24px; padding: 25mm; box-sizing: border-box; box-shadow: 0 1px 4px rgba(0,0,0,.2); position: relative; overflow: hidden; }
+p { margin: 0; line-height: 1.3; }
+p.blank { min-height: 1.3em; }
+p.spacer { min-height: 12pt; }
+h2, h3 { font-weight: normal; margin: 0; }
+p.field .value, p.text { white-space: pre-wrap; }
+nav.contents a { display: block; text-decoration: none; margin-bottom: 6pt; }
+ul.list { margin-top: 0; margin-bottom: 0; padding-left: 0; list-style: none; }
+ul.list li::before { content: "\2022  "; }
+table { width: 100%; border-collapse: collapse; }
+td { border: 1px solid #000; padding: 2px 4px; vertical-align: top; }
+td.open-left { border-left: none; }
+td.open-right { border-right: none; }
+img { max-width: 100%; }
+.watermark { position: absolute; top: 45%; left: 50%; transform: translate(-50%, -50%) rotate(-45deg); font: bold 96pt Calibri, sans-serif; color: rgba(192,192,192,.5); pointer-events: none; white-space: nowrap; }
+@media print {
+  body { background: none; padding: 0; }
+  .page { margin: 0; box-shadow: none; page-break-after: always; }
+}
+"#;
+
+const LANDSCAPE_STYLE: &str = r#"
+.page { width: 297mm; min-height: 210mm; }
+@page { size: A4 landscape; }
+"#;
+
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Font and colour names from a branding profile, kept from breaking out of
+// the declaration
+fn css_value(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| c.is_alphanumeric() || matches!(c, ' ' | '#' | '-' | ',' | '.'))
+        .collect()
+}
+
+fn style_rule(out: &mut String, name: StyleName, def: &StyleDef, script_fonts: &[&str]) {
+    let _ = write!(out, ".s-{} {{ ", name.id());
+    if let Some(bold) = def.bold {
+        let _ = write!(out, "font-weight:{};", if bold { "bold" } else { "normal" });
+    }
+    if let Some(italic) = def.italic {
+        let _ = write!(out, "font-style:{};", if italic { "italic" } else { "normal" });
+    }
+    if let Some(size) = def.size {
+        // half-points
+        let _ = write!(out, "font-size:{}pt;", size as f32 / 2.0);
+    }
+    if let Some(font) = &def.font {
+        let families: Vec<String> = std::iter::once(font.as_str())
+            .chain(script_fonts.iter().copied())
+            .map(css_value)
+            .collect();
+        let _ = write!(out, "font-family:{};", families.join(","));
+    }
+    if let Some(color) = &def.color {
+        let _ = write!(out, "color:{};", css_value(color));
+    }
+    out.push_str(align_css(def.align));
+    if let Some(indent) = def.indent {
+        // twips
+        let _ = write!(out, "margin-left:{}pt;", indent / 20);
+    }
+    out.push_str(" }\n");
+}
+
+fn style_sheet_css(styles: &StyleSheet) -> String {
+    let ScriptFonts { complex, east_asian } = &styles.script_fonts;
+    let script_fonts: Vec<&str> = [complex, east_asian].into_iter().flatten().map(String::as_str).collect();
+    let mut css = String::new();
+    for (name, def) in styles.iter() {
+        style_rule(&mut css, name, def, &script_fonts);
+    }
+    css
+}
+
+fn class(style: StyleName) -> String {
+    format!("s-{}", style.id())
+}
+
+fn align_css(align: Option<Align>) -> &'static str {
+    match align {
+        Some(Align::Center) => "text-align:center;",
+        _ => "",
+    }
+}
+
+fn span(out: &mut String, span: &Span) {
+    let style = &span.style;
+    let mut css = String::new();
+    if style.bold {
+        css.push_str("font-weight:bold;");
+    }
+    if style.italic {
+        css.push_str("font-style:italic;");
+    }
+    match (style.underline, style.strike) {
+        (true, true) => css.push_str("text-decoration:underline line-through;"),
+        (true, false) => css.push_str("text-decoration:underline;"),
+        (false, true) => css.push_str("text-decoration:line-through;"),
+        (false, false) => {}
+    }
+    if let Some(size) = style.size {
+        // half-points
+        let _ = write!(css, "font-size:{}pt;", size as f32 / 2.0);
+    }
+    if let Some(font) = &style.font {
+        let _ = write!(css, "font-family:{};", escape(font));
+    }
+    if let Some(color) = &style.color {
+        let _ = write!(css, "color:{};", escape(color));
+    }
+    match style.character {
+        Some(character) => {
+            let _ = write!(out, r#"<span class="{}" style="{}">{}</span>"#, class(character), css, escape(&span.text));
+        }
+        None => {
+            let _ = write!(out, r#"<span style="{}">{}</span>"#, css, escape(&span.text));
+        }
+    }
+}
+
+fn paragraph(out: &mut String, para: &Paragraph) {
+    let mut css = align_css(para.align).to_string();
+    if let Some(indent) = para.indent {
+        // twips
+        let _ = write!(css, "margin-left:{}pt;", indent / 20);
+    }
+    match para.style {
+        Some(style) => {
+            let _ = write!(out, r#"<p class="{}" style="{}">"#, class(style), css);
+        }
+        None => {
+            let _ = write!(out, r#"<p style="{}">"#, css);
+        }
+    }
+    for s in &para.spans {
+        span(out, s);
+    }
+    out.push_str("</p>\n");
+}
+
+fn cell(out: &mut String, cell: &Cell) {
+    let mut classes = Vec::new();
+    if cell.open_left {
+        classes.push("open-left");
+    }
+    if cell.open_right {
+        classes.push("open-right");
+    }
+    let _ = write!(out, r#"<td class="{}">"#, classes.join(" "));
+    for s in &cell.spans {
+        span(out, s);
+    }
+    out.push_str("</td>");
+}
+
+fn table(out: &mut String, table: &Table) {
+    let _ = writeln!(out, r#"<table class="{}">"#, class(StyleName::TableCell));
+    for row in &table.rows {
+        out.push_str("<tr>");
+        for c in row {
+            cell(out, c);
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</table>\n");
+}
+
+fn image_mime(data: &[u8]) -> &'static str {
+    if data.starts_with(&[0xFF, 0xD8]) {
+        "image/jpeg"
+    } else {
+        "image/png"
+    }
+}
+
+fn image_row(out: &mut String, images: &[Image]) {
+    out.push_str(r#"<p style="text-align:center;">"#);
+    for (i, image) in images.iter().enumerate() {
+        let mut css = "vertical-align:bottom;".to_string();
+        if i > 0 {
+            css.push_str("margin-left:24pt;");
+        }
+        if let Some(height) = image.height {
+            // twips
+            let _ = write!(css, "height:{}pt;", height as f64 / 20.0);
+        }
+        let _ = write!(
+            out,
+            r#"<img src="data:{};base64,{}" style="{}" alt="">"#,
+            image_mime(&image.data),
+            STANDARD.encode(&image.data),
+            css
+        );
+    }
+    out.push_str("</p>\n");
+}
+
+fn open_page(out: &mut String, watermark: Option<&str>) {
+    out.push_str("<div class=\"page\">\n");
+    if let Some(text) = watermark {
+        let _ = writeln!(out, r#"<div class="watermark">{}</div>"#, escape(text));
+    }
+}
+
+fn anchor(index: usize) -> String {
+    format!("heading-{}", index + 1)
+}
+
+fn contents(out: &mut String, outline: &[(usize, &str)]) {
+    out.push_str("<nav class=\"contents\">\n");
+    for (index, (level, text)) in outline.iter().enumerate() {
+        let style = if *level == 1 { StyleName::TOC1 } else { StyleName::TOC2 };
+        let _ = writeln!(
+            out,
+            r##"<a class="{}" href="#{}">{}</a>"##,
+            class(style),
+            anchor(index),
+            escape(text)
+        );
+    }
+    out.push_str("</nav>\n");
+}
+
+// Position in `Document::outline` of the next heading, and the outline
+// itself for the contents page
+struct Outline<'a> {
+    entries: Vec<(usize, &'a str)>,
+    next: usize,
+}
+
+impl Outline<'_> {
+    fn next_anchor(&mut self) -> String {
+        self.next += 1;
+        anchor(self.next - 1)
+    }
+}
+
+fn add_blocks(out: &mut String, blocks: &[Block], outline: &mut Outline) {
+    for block in blocks {
+        match block {
+            Block::Paragraph(para) => paragraph(out, para),
+            Block::Blank => out.push_str("<p class=\"blank\"></p>\n"),
+            Block::Spacer => out.push_str("<p class=\"spacer\"></p>\n"),
+            Block::Heading(text) => {
+                let _ = writeln!(
+                    out,
+                    r#"<h3 class="{}" id="{}">{}</h3>"#,
+                    class(StyleName::Heading2),
+                    outline.next_anchor(),
+                    escape(text)
+                );
+            }
+            Block::Text(text) => {
+                let _ = writeln!(out, r#"<p class="text {}">{}</p>"#, class(StyleName::FieldValue), escape(text));
+            }
+            Block::Field { label, value } => {
+                let _ = writeln!(
+                    out,
+                    r#"<p class="field"><span class="{}">{}</span><span class="value {}">{}</span></p>"#,
+                    class(StyleName::FieldLabel),
+                    escape(label),
+                    class(StyleName::FieldValue),
+                    escape(value)
+                );
+            }
+            Block::List(items) => {
+                let _ = write!(out, r#"<ul class="list {}">"#, class(StyleName::ListItem));
+                for item in items {
+                    let _ = write!(out, "<li>{}</li>", escape(item));
+                }
+                out.push_str("</ul>\n");
+            }
+            Block::Table(t) => table(out, t),
+            Block::Image(data) => {
+                let _ = writeln!(
+                    out,
+                    r#"<p style="text-align:center;"><img src="data:{};base64,{}" alt=""></p>"#,
+                    image_mime(data),
+                    STANDARD.encode(data)
+                );
+            }
+            Block::ImageRow(images) => image_row(out, images),
+            Block::Contents => contents(out, &outline.entries),
+        }
+    }
+}
+
+fn add_section(out: &mut String, section: &Section, outline: &mut Outline, watermark: Option<&str>) {
+    if section.new_page {
+        out.push_str("</div>\n");
+        open_page(out, watermark);
+    }
+    if let Some(title) = &section.title {
+        let _ = writeln!(
+            out,
+            r#"<h2 class="{}" id="{}">{}</h2>"#,
+            class(StyleName::Heading1),
+            outline.next_anchor(),
+            escape(title)
+        );
+    }
+    add_blocks(out, &section.blocks, outline);
+}
+
+pub fn to_html(document: &Document, watermark: Option<&str>) -> String {
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}{}{}</style>\n</head>\n<body>\n",
+        escape(&document.title),
+        STYLE,
+        if document.landscape { LANDSCAPE_STYLE } else { "" },
+        style_sheet_css(&document.styles)
+    );
+    open_page(&mut out, watermark);
+
+    let mut outline = Outline {
+        entries: document.outline(),
+        next: 0,
+    };
+    for section in &document.sections {
+        add_section(&mut out, section, &mut outline, watermark);
+    }
+
+    out.push_str("</div>\n</body>\n</html>\n");
+    out
+}
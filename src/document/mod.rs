@@ -0,0 +1,491 @@
+use crate::config::TemplateConfig;
+use crate::models::Submission;
+use crate::page1::page1_content;
+use crate::page2::{page2_content, page2_content_signatures};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize, Serializer};
+
+pub mod docx;
+pub mod html;
+pub mod styles;
+
+pub use styles::{ScriptFonts, StyleDef, StyleName, StyleSheet};
+
+// Format-neutral description of a generated document. `page1` and `page2`
+// build the proposal from a Submission and `diff` builds the redline
+// report; the DOCX and HTML backends only decide how each block looks, so
+// every output format follows the same structure. The model serializes to
+// JSON, which lets the structure be inspected without parsing DOCX.
+//
+// Formatting comes from the document's named styles (see `styles`); a
+// span's TextStyle only carries what differs from its paragraph's style.
+//
+// Sizes are in half-points and indents/widths in twips, as in Word.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Align {
+    Left,
+    Center,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TextStyle {
+    // Named character style (FieldLabel or FieldValue) the run takes its
+    // font from, for text outside the blocks that apply them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub character: Option<StyleName>,
+    #[serde(skip_serializing_if = "is_false")]
+    pub bold: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    pub italic: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    pub underline: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    pub strike: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub font: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+fn base64_data<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&STANDARD.encode(data))
+}
+
+impl TextStyle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn character(mut self, style: StyleName) -> Self {
+        self.character = Some(style);
+        self
+    }
+
+    pub fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    pub fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+
+    pub fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    pub fn strike(mut self) -> Self {
+        self.strike = true;
+        self
+    }
+
+    pub fn size(mut self, size: usize) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn color(mut self, color: &str) -> Self {
+        self.color = Some(color.to_string());
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Span {
+    pub text: String,
+    pub style: TextStyle,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Paragraph {
+    pub spans: Vec<Span>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub style: Option<StyleName>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub align: Option<Align>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indent: Option<i32>,
+}
+
+impl Paragraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn centered() -> Self {
+        Self::new().align(Align::Center)
+    }
+
+    pub fn styled(style: StyleName) -> Self {
+        Self {
+            style: Some(style),
+            ..Self::default()
+        }
+    }
+
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = Some(align);
+        self
+    }
+
+    pub fn text(mut self, text: impl Into<String>, style: TextStyle) -> Self {
+        self.spans.push(Span {
+            text: text.into(),
+            style,
+        });
+        self
+    }
+
+    pub fn span(mut self, span: Span) -> Self {
+        self.spans.push(span);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Cell {
+    // Unstyled spans use the TableCell style
+    pub spans: Vec<Span>,
+    // Column width in twips, set on the first row
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<usize>,
+    // Drop the border shared with the neighbouring cell
+    #[serde(skip_serializing_if = "is_false")]
+    pub open_left: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    pub open_right: bool,
+}
+
+impl Cell {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self::styled(vec![Span {
+            text: text.into(),
+            style: TextStyle::new(),
+        }])
+    }
+
+    pub fn styled(spans: Vec<Span>) -> Self {
+        Self {
+            spans,
+            ..Self::default()
+        }
+    }
+
+    pub fn bold(text: &str) -> Self {
+        Self::styled(vec![Span {
+            text: text.to_string(),
+            style: TextStyle::new().bold(),
+        }])
+    }
+
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Table {
+    // The first row is the header
+    pub rows: Vec<Vec<Cell>>,
+}
+
+// PNG/JPEG bytes scaled to `height` (width follows), or at natural size
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Image {
+    #[serde(serialize_with = "base64_data")]
+    pub data: Vec<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<usize>,
+}
+
+impl Image {
+    pub fn new(data: Vec<u8>) -> Self {
+        Self { data, height: None }
+    }
+
+    pub fn height(mut self, height: usize) -> Self {
+        self.height = Some(height);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Block {
+    Paragraph(Paragraph),
+    // Empty line used for vertical layout
+    Blank,
+    // Gap between numbered items
+    Spacer,
+    // Numbered template item ("1. Project Title"), listed in the outline and
+    // the table of contents (Heading2)
+    Heading(String),
+    // The applicant's text under a heading (FieldValue)
+    Text(String),
+    // FieldLabel followed by the applicant's text on the same line
+    Field { label: String, value: String },
+    // Bullet lines under a field (ListItem)
+    List(Vec<String>),
+    Table(Table),
+    // PNG/JPEG bytes, shown centered at natural size
+    Image(#[serde(serialize_with = "base64_data")] Vec<u8>),
+    // Images side by side on one centered line, e.g. the cover's logo and
+    // verification QR code
+    ImageRow(Vec<Image>),
+    // Table of contents of the whole document's outline
+    Contents,
+}
+
+impl Block {
+    pub fn field(label: &str, value: &str) -> Self {
+        Block::Field {
+            label: label.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    // A paragraph of plain text in one of the template's styles
+    pub fn styled(text: &str, style: StyleName) -> Self {
+        Block::Paragraph(Paragraph::styled(style).text(text, TextStyle::new()))
+    }
+}
+
+// Dates in generated documents, e.g. "01 April 2025"
+pub fn format_date(date: NaiveDate) -> String {
+    date.format("%d %B %Y").to_string()
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Section {
+    // Heading1 title such as "Section A"; untitled sections just group blocks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "is_false")]
+    pub new_page: bool,
+    pub blocks: Vec<Block>,
+}
+
+impl Section {
+    pub fn new(blocks: Vec<Block>) -> Self {
+        Self {
+            blocks,
+            ..Self::default()
+        }
+    }
+
+    pub fn titled(title: &str, blocks: Vec<Block>) -> Self {
+        Self {
+            title: Some(title.to_string()),
+            ..Self::new(blocks)
+        }
+    }
+
+    // Start the section on a new page
+    pub fn new_page(mut self) -> Self {
+        self.new_page = true;
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Document {
+    pub title: String,
+    pub styles: StyleSheet,
+    pub sections: Vec<Section>,
+    // A4 landscape instead of portrait, for wide tables
+    #[serde(skip_serializing_if = "is_false")]
+    pub landscape: bool,
+}
+
+impl Document {
+    // Section titles (level 1) and headings (level 2) in document order.
+    // Backends number their anchors by position in this list.
+    pub fn outline(&self) -> Vec<(usize, &str)> {
+        let mut outline = Vec::new();
+        for section in &self.sections {
+            if let Some(title) = &section.title {
+                outline.push((1, title.as_str()));
+            }
+            for block in &section.blocks {
+                if let Block::Heading(text) = block {
+                    outline.push((2, text.as_str()));
+                }
+            }
+        }
+        outline
+    }
+}
+
+// Page listing the proposal's sections, placed after the cover
+fn contents_page() -> Section {
+    Section::new(vec![
+        Block::Paragraph(Paragraph::styled(StyleName::TOCHeading).text("Contents", TextStyle::new())),
+        Block::Spacer,
+        Block::Contents,
+    ])
+    .new_page()
+}
+
+// The complete SEED proposal: cover page (optionally with the verification
+// QR code), optionally a table of contents, Sections A and B with the
+// budget table, and the signature block, styled with `styles` (the house
+// style or a branding profile).
+pub fn proposal(
+    submission: &Submission,
+    template: &TemplateConfig,
+    contents: bool,
+    qr: bool,
+    styles: &StyleSheet,
+) -> Document {
+    let mut sections = vec![Section::new(page1_content(submission, template, qr))];
+    if contents {
+        sections.push(contents_page());
+    }
+    sections.extend(page2_content(submission));
+    sections.push(Section::new(page2_content_signatures()));
+
+    Document {
+        title: submission
+            .project_title
+            .clone()
+            .unwrap_or_else(|| submission.unique_id.clone()),
+        styles: styles.clone(),
+        sections,
+        landscape: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(spans: &[Span]) -> String {
+        spans.iter().map(|span| span.text.as_str()).collect()
+    }
+
+    // Styled paragraphs as (style, text)
+    fn paragraphs(section: &Section) -> Vec<(Option<StyleName>, String)> {
+        section
+            .blocks
+            .iter()
+            .filter_map(|block| match block {
+                Block::Paragraph(paragraph) => Some((paragraph.style, text(&paragraph.spans))),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn proposal_follows_the_template() {
+        let submission: Submission = serde_json::from_str(include_str!("../../mock_submission.json")).unwrap();
+        let document = proposal(&submission, &TemplateConfig::default(), false, false, &StyleSheet::default());
+        assert_eq!(document.title, "Smart Energy Management System for Institutional Buildings");
+
+        let titles: Vec<_> = document.sections.iter().map(|section| section.title.as_deref()).collect();
+        assert_eq!(titles, vec![None, Some("Section A"), Some("Section B"), None]);
+        assert!(document.sections[1].new_page);
+
+        // cover page
+        let cover = paragraphs(&document.sections[0]);
+        let styled = |style: StyleName| -> Vec<&str> {
+            cover.iter().filter(|(s, _)| *s == Some(style)).map(|(_, text)| text.as_str()).collect()
+        };
+        assert_eq!(styled(StyleName::Title), vec!["Smart Energy Management System for Institutional Buildings"]);
+        assert_eq!(styled(StyleName::Subtitle), vec!["COE-DSAI SEED GRANT PROPOSAL"]);
+        assert_eq!(
+            styled(StyleName::CoverName),
+            vec!["pmahajan1_be22@thapar.edu", "asingh_be22@thapar.edu", "rjain_be22@thapar.edu"]
+        );
+
+        // items 1-7 in Section A, 8-15 in Section B and 16 before the signature
+        let headings: Vec<(usize, &str)> = document
+            .sections
+            .iter()
+            .enumerate()
+            .flat_map(|(index, section)| {
+                section.blocks.iter().filter_map(move |block| match block {
+                    Block::Heading(heading) => Some((index, heading.as_str())),
+                    _ => None,
+                })
+            })
+            .collect();
+        assert_eq!(headings.len(), 16);
+        for (number, (section, heading)) in headings.iter().enumerate() {
+            let number = number + 1;
+            assert!(heading.starts_with(&format!("{}. ", number)), "{}", heading);
+            let expected = match number {
+                1..=7 => 1,
+                8..=15 => 2,
+                _ => 3,
+            };
+            assert_eq!(*section, expected, "{}", heading);
+        }
+        assert_eq!(headings[0].1, "1. Project Title");
+        assert_eq!(headings[14].1, "15. Budget requirement with justification (Consumables, Equipment, Contingency)");
+
+        // budget table: header, then each category followed by its items
+        let table = document.sections[2]
+            .blocks
+            .iter()
+            .find_map(|block| match block {
+                Block::Table(table) => Some(table),
+                _ => None,
+            })
+            .unwrap();
+        let rows: Vec<Vec<String>> =
+            table.rows.iter().map(|row| row.iter().map(|cell| text(&cell.spans)).collect()).collect();
+        assert_eq!(rows[0], vec!["", "Item", "Year 1", "Year 2", "Year 3", "Total", "Justification"]);
+        assert_eq!(rows[1][..2], ["1", "Recurring"]);
+        assert_eq!(
+            rows[2],
+            vec![
+                "",
+                "Manpower",
+                "160000",
+                "160000",
+                "160000",
+                "480000",
+                "Research assistants required for deployment and data collection"
+            ]
+        );
+        let items: Vec<&str> = rows.iter().map(|row| row[1].as_str()).filter(|item| !item.is_empty()).collect();
+        assert_eq!(
+            items,
+            vec![
+                "Item",
+                "Recurring",
+                "Manpower",
+                "Consumables",
+                "Contingency",
+                "Travel",
+                "Non-Recurring",
+                "Sensor Network",
+                "GPU Requirement",
+                "Computing Infrastructure"
+            ]
+        );
+
+        // signatures
+        let signatures = paragraphs(&document.sections[3]);
+        assert_eq!(signatures, vec![(Some(StyleName::Signature), "Signature of the Applicant".to_string())]);
+    }
+}
@@ -0,0 +1,285 @@
+use super::Align;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Named styles of the generated documents. The DOCX backend writes them to
+// the style sheet (so editors can restyle the proposal in Word) and the
+// HTML backend to CSS classes; blocks only reference them by name. Branding
+// profiles override the definitions.
+//
+// A style's font is the Latin one. Text in other scripts (applicants write
+// names and abstracts in Hindi and Punjabi) uses the sheet's script fonts,
+// which the DOCX sets as the document defaults for Word's complex-script and
+// east Asian font slots.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum StyleName {
+    // Cover page
+    Title,
+    Subtitle,
+    CoverHeading,
+    CoverText,
+    CoverName,
+    Institution,
+    CoverAddress,
+    // Body
+    Heading1,
+    Heading2,
+    FieldLabel,
+    FieldValue,
+    ListItem,
+    TableCell,
+    Signature,
+    // Contents page
+    TOCHeading,
+    TOC1,
+    TOC2,
+}
+
+impl StyleName {
+    pub const ALL: [StyleName; 17] = [
+        StyleName::Title,
+        StyleName::Subtitle,
+        StyleName::CoverHeading,
+        StyleName::CoverText,
+        StyleName::CoverName,
+        StyleName::Institution,
+        StyleName::CoverAddress,
+        StyleName::Heading1,
+        StyleName::Heading2,
+        StyleName::FieldLabel,
+        StyleName::FieldValue,
+        StyleName::ListItem,
+        StyleName::TableCell,
+        StyleName::Signature,
+        StyleName::TOCHeading,
+        StyleName::TOC1,
+        StyleName::TOC2,
+    ];
+
+    // Style ID in the DOCX and CSS class suffix
+    pub fn id(self) -> &'static str {
+        match self {
+            StyleName::Title => "Title",
+            StyleName::Subtitle => "Subtitle",
+            StyleName::CoverHeading => "CoverHeading",
+            StyleName::CoverText => "CoverText",
+            StyleName::CoverName => "CoverName",
+            StyleName::Institution => "Institution",
+            StyleName::CoverAddress => "CoverAddress",
+            StyleName::Heading1 => "Heading1",
+            StyleName::Heading2 => "Heading2",
+            StyleName::FieldLabel => "FieldLabel",
+            StyleName::FieldValue => "FieldValue",
+            StyleName::ListItem => "ListItem",
+            StyleName::TableCell => "TableCell",
+            StyleName::Signature => "Signature",
+            StyleName::TOCHeading => "TOCHeading",
+            StyleName::TOC1 => "TOC1",
+            StyleName::TOC2 => "TOC2",
+        }
+    }
+
+    // Name shown in Word's style gallery; Word's built-in styles keep their
+    // built-in names so Word treats them as such
+    pub fn display_name(self) -> &'static str {
+        match self {
+            StyleName::Title => "Title",
+            StyleName::Subtitle => "Subtitle",
+            StyleName::CoverHeading => "Cover Heading",
+            StyleName::CoverText => "Cover Text",
+            StyleName::CoverName => "Cover Name",
+            StyleName::Institution => "Institution",
+            StyleName::CoverAddress => "Cover Address",
+            StyleName::Heading1 => "heading 1",
+            StyleName::Heading2 => "heading 2",
+            StyleName::FieldLabel => "Field Label",
+            StyleName::FieldValue => "Field Value",
+            StyleName::ListItem => "List Item",
+            StyleName::TableCell => "Table Cell",
+            StyleName::Signature => "Signature",
+            StyleName::TOCHeading => "TOC Heading",
+            StyleName::TOC1 => "toc 1",
+            StyleName::TOC2 => "toc 2",
+        }
+    }
+
+    // Character styles apply to runs inside a paragraph; the rest are
+    // paragraph styles
+    pub fn is_character(self) -> bool {
+        matches!(self, StyleName::FieldLabel | StyleName::FieldValue)
+    }
+
+    // Outline level (1-based) of the heading styles
+    pub fn outline_level(self) -> Option<usize> {
+        match self {
+            StyleName::Heading1 => Some(1),
+            StyleName::Heading2 => Some(2),
+            _ => None,
+        }
+    }
+}
+
+// Formatting of one named style. In a branding profile every field is
+// optional and replaces only that part of the default definition.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StyleDef {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub font: Option<String>,
+    // Half-points
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bold: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub italic: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub align: Option<Align>,
+    // Left indent in twips
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indent: Option<i32>,
+}
+
+impl StyleDef {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn font(mut self, font: &str) -> Self {
+        self.font = Some(font.to_string());
+        self
+    }
+
+    fn size(mut self, size: usize) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    fn color(mut self, color: &str) -> Self {
+        self.color = Some(color.to_string());
+        self
+    }
+
+    fn bold(mut self) -> Self {
+        self.bold = Some(true);
+        self
+    }
+
+    fn center(mut self) -> Self {
+        self.align = Some(Align::Center);
+        self
+    }
+
+    fn indent(mut self, indent: i32) -> Self {
+        self.indent = Some(indent);
+        self
+    }
+
+    // This definition with the fields set in `other` replaced
+    pub fn merge(&self, other: &StyleDef) -> StyleDef {
+        StyleDef {
+            font: other.font.clone().or_else(|| self.font.clone()),
+            size: other.size.or(self.size),
+            color: other.color.clone().or_else(|| self.color.clone()),
+            bold: other.bold.or(self.bold),
+            italic: other.italic.or(self.italic),
+            align: other.align.or(self.align),
+            indent: other.indent.or(self.indent),
+        }
+    }
+}
+
+// Fonts for the non-Latin font slots. Devanagari and Gurmukhi are complex
+// scripts in Word's terms; LibreOffice falls back per glyph to another
+// installed font when the named one lacks a character.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct ScriptFonts {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub complex: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub east_asian: Option<String>,
+}
+
+impl ScriptFonts {
+    // These fonts with the ones set in `other` replaced
+    pub fn merge(&self, other: &ScriptFonts) -> ScriptFonts {
+        ScriptFonts {
+            complex: other.complex.clone().or_else(|| self.complex.clone()),
+            east_asian: other.east_asian.clone().or_else(|| self.east_asian.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StyleSheet {
+    #[serde(flatten)]
+    styles: BTreeMap<StyleName, StyleDef>,
+    pub script_fonts: ScriptFonts,
+}
+
+// The proposal's house style
+impl Default for StyleSheet {
+    fn default() -> Self {
+        let styles = StyleName::ALL
+            .iter()
+            .map(|&name| {
+                let def = match name {
+                    StyleName::Title => StyleDef::new().bold().size(48).center(),
+                    // Burgundy to match the printed form
+                    StyleName::Subtitle => StyleDef::new().bold().size(48).color("#800020").center(),
+                    StyleName::CoverHeading => StyleDef::new().bold().size(48).center(),
+                    StyleName::CoverText => StyleDef::new().size(36).center(),
+                    StyleName::CoverName => StyleDef::new().bold().size(40).center(),
+                    // Blue to match the printed form
+                    StyleName::Institution => StyleDef::new().bold().size(36).color("#0066CC").center(),
+                    StyleName::CoverAddress => StyleDef::new().bold().size(36).center(),
+                    StyleName::Heading1 => StyleDef::new().font("Arial").bold().size(48).color("#000000").center(),
+                    StyleName::Heading2 | StyleName::FieldLabel | StyleName::TOC1 => {
+                        StyleDef::new().font("Calibri").bold().size(28).color("#000000")
+                    }
+                    StyleName::FieldValue => StyleDef::new().font("Georgia").size(28).color("#333333"),
+                    StyleName::ListItem => StyleDef::new().font("Calibri").size(28).color("#000000").indent(500),
+                    StyleName::TableCell => StyleDef::new().font("Calibri").size(28).color("#333333"),
+                    StyleName::Signature => StyleDef::new().font("Georgia").size(24).color("#333333"),
+                    StyleName::TOCHeading => StyleDef::new().font("Arial").bold().size(48).color("#000000").center(),
+                    StyleName::TOC2 => StyleDef::new().font("Calibri").size(28).color("#000000").indent(400),
+                };
+                (name, def)
+            })
+            .collect();
+        Self {
+            styles,
+            // both ship in the Docker image (fonts-noto-core, fonts-noto-cjk)
+            script_fonts: ScriptFonts {
+                complex: Some("Noto Sans Devanagari".to_string()),
+                east_asian: Some("Noto Sans CJK SC".to_string()),
+            },
+        }
+    }
+}
+
+impl StyleSheet {
+    pub fn get(&self, name: StyleName) -> &StyleDef {
+        &self.styles[&name]
+    }
+
+    // The house style with a branding profile's definitions merged in
+    pub fn with_overrides(overrides: &BTreeMap<StyleName, StyleDef>, script_fonts: &ScriptFonts) -> Self {
+        let mut sheet = Self::default();
+        for (name, def) in overrides {
+            let merged = sheet.get(*name).merge(def);
+            sheet.styles.insert(*name, merged);
+        }
+        sheet.script_fonts = sheet.script_fonts.merge(script_fonts);
+        sheet
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (StyleName, &StyleDef)> {
+        self.styles.iter().map(|(name, def)| (*name, def))
+    }
+}
@@ -0,0 +1,186 @@
+use crate::document::{Block, Cell, Document, Paragraph, Section, Span, StyleName, StyleSheet, Table, TextStyle};
+use crate::models::Submission;
+use crate::page2::calculate_total_budget;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+
+// Reviewer evaluation form: the scoring sheet a reviewer fills in for one
+// proposal, pre-filled with the proposal's details. The scoring criteria
+// come from the JSON file in EVALUATION_CRITERIA, a list such as
+//
+//   [{"name": "Novelty", "description": "Originality of the idea", "maxScore": 20}]
+//
+// and default to the centre's standard seed grant criteria.
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct Criterion {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub max_score: u32,
+}
+
+impl Criterion {
+    fn new(name: &str, description: &str, max_score: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            description: Some(description.to_string()),
+            max_score,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoringCriteria {
+    pub criteria: Vec<Criterion>,
+}
+
+// Out of 100
+impl Default for ScoringCriteria {
+    fn default() -> Self {
+        Self {
+            criteria: vec![
+                Criterion::new("Scientific merit", "Novelty and technical soundness of the proposal", 20),
+                Criterion::new("Objectives and methodology", "Clarity of the objectives and suitability of the methods", 20),
+                Criterion::new("Relevance", "Relevance to data science and artificial intelligence", 15),
+                Criterion::new("Feasibility", "Achievability of the work plan and time schedule", 15),
+                Criterion::new("Investigators", "Competence and track record of the team", 10),
+                Criterion::new("Budget", "Justification of the budget requested", 10),
+                Criterion::new("Impact", "Expected deliverables and their societal or industry impact", 10),
+            ],
+        }
+    }
+}
+
+fn load_criteria(path: &str) -> Result<Vec<Criterion>, String> {
+    let data = fs::read(path).map_err(|e| e.to_string())?;
+    let criteria: Vec<Criterion> = serde_json::from_slice(&data).map_err(|e| e.to_string())?;
+    if criteria.is_empty() {
+        return Err("no criteria".to_string());
+    }
+    if let Some(criterion) = criteria.iter().find(|c| c.name.trim().is_empty() || c.max_score == 0) {
+        return Err(format!("criterion {:?} needs a name and a maxScore above 0", criterion.name));
+    }
+    Ok(criteria)
+}
+
+impl ScoringCriteria {
+    // Reads the criteria file; without one the default criteria apply, and a
+    // file that doesn't parse is ignored with a message
+    pub fn from_env() -> Self {
+        let path = env::var("EVALUATION_CRITERIA").unwrap_or_else(|_| "evaluation_criteria.json".to_string());
+        match load_criteria(&path) {
+            Ok(criteria) => {
+                println!("Loaded {} evaluation criteria from {}", criteria.len(), path);
+                Self { criteria }
+            }
+            Err(e) => {
+                if fs::metadata(&path).is_ok() {
+                    println!("Ignoring evaluation criteria {}: {}", path, e);
+                }
+                Self::default()
+            }
+        }
+    }
+
+    pub fn total(&self) -> u32 {
+        self.criteria.iter().map(|criterion| criterion.max_score).sum()
+    }
+}
+
+pub fn evaluation_filename(submission: &Submission, extension: &str) -> String {
+    format!("evaluation_{}.{}", submission.unique_id, extension)
+}
+
+fn scoring_table(criteria: &ScoringCriteria) -> Table {
+    let mut rows = vec![vec![
+        Cell::bold("#").width(500),
+        Cell::bold("Criterion").width(3500),
+        Cell::bold("Max. Score").width(1200),
+        Cell::bold("Score").width(1300),
+        Cell::bold("Remarks").width(2500),
+    ]];
+    for (index, criterion) in criteria.criteria.iter().enumerate() {
+        let mut spans = vec![Span {
+            text: criterion.name.clone(),
+            style: TextStyle::new().bold(),
+        }];
+        if let Some(description) = criterion.description.as_deref().filter(|d| !d.is_empty()) {
+            spans.push(Span {
+                text: format!(": {}", description),
+                style: TextStyle::new(),
+            });
+        }
+        rows.push(vec![
+            Cell::new((index + 1).to_string()),
+            Cell::styled(spans),
+            Cell::new(criterion.max_score.to_string()),
+            Cell::empty(),
+            Cell::empty(),
+        ]);
+    }
+    rows.push(vec![
+        Cell::empty(),
+        Cell::bold("Total"),
+        Cell::bold(&criteria.total().to_string()),
+        Cell::empty(),
+        Cell::empty(),
+    ]);
+    Table { rows }
+}
+
+// Tick boxes for the reviewer's overall recommendation
+const RECOMMENDATIONS: [&str; 3] = [
+    "Recommended for funding",
+    "Recommended subject to revisions",
+    "Not recommended",
+];
+
+pub fn evaluation_form(submission: &Submission, criteria: &ScoringCriteria, styles: &StyleSheet) -> Document {
+    let text = |value: &Option<String>| value.clone().unwrap_or_default();
+    let trl_level = submission.trl_level.as_ref().map_or_else(String::new, |s| s.to_string());
+
+    let mut blocks = vec![
+        Block::Paragraph(Paragraph::styled(StyleName::CoverText).text("COE-DSAI Seed Grant Proposal", TextStyle::new())),
+        Block::Spacer,
+        Block::Heading("1. Proposal Details".to_string()),
+        Block::field("Unique ID: ", &submission.unique_id),
+        Block::field("Project Title: ", &text(&submission.project_title)),
+        Block::field("Track: ", &submission.track),
+        Block::field("TRL Level: ", &trl_level),
+        Block::field("Total Budget: ", &calculate_total_budget(submission)),
+        Block::Spacer,
+        Block::Heading("2. Scoring".to_string()),
+        Block::Table(scoring_table(criteria)),
+        Block::Spacer,
+        Block::Heading("3. Overall Recommendation".to_string()),
+    ];
+    for recommendation in RECOMMENDATIONS {
+        blocks.push(Block::Paragraph(
+            Paragraph::styled(StyleName::ListItem).text(format!("☐  {}", recommendation), TextStyle::new()),
+        ));
+    }
+    blocks.push(Block::Spacer);
+    blocks.push(Block::Heading("4. Comments and Suggestions".to_string()));
+    blocks.extend(vec![Block::Blank; 6]);
+    blocks.push(Block::Heading("5. Reviewer".to_string()));
+    blocks.push(Block::Text(
+        "I declare that I have no conflict of interest with the investigators of this proposal.".to_string(),
+    ));
+    blocks.push(Block::field("Name: ", ""));
+    blocks.push(Block::field("Designation / Department: ", ""));
+    blocks.push(Block::field("Date: ", ""));
+    blocks.extend(vec![Block::Blank; 2]);
+    blocks.push(Block::Paragraph(
+        Paragraph::styled(StyleName::Signature).text("Signature of the Reviewer", TextStyle::new()),
+    ));
+
+    Document {
+        title: format!("Reviewer Evaluation Form: {}", submission.unique_id),
+        styles: styles.clone(),
+        sections: vec![Section::titled("Reviewer Evaluation Form", blocks)],
+        landscape: false,
+    }
+}
@@ -0,0 +1,121 @@
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::fmt;
+
+// Deserializers for MongoDB extended JSON as exported by the DMS backend.
+// Both the canonical form ({"$date": {"$numberLong": "..."}}) and the relaxed
+// form ({"$date": "2025-03-31T20:47:25.175Z"}) are accepted, as are plain
+// strings and numbers since older submissions were stored without wrappers.
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ObjectId(String);
+
+impl fmt::Display for ObjectId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for ObjectId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        let hex = match &value {
+            Value::String(s) => s.as_str(),
+            Value::Object(map) => map
+                .get("$oid")
+                .and_then(Value::as_str)
+                .ok_or_else(|| D::Error::custom("expected {\"$oid\": \"...\"}"))?,
+            _ => return Err(D::Error::custom("expected an ObjectId")),
+        };
+
+        if hex.len() != 24 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(D::Error::custom(format!("invalid ObjectId: {}", hex)));
+        }
+
+        Ok(ObjectId(hex.to_lowercase()))
+    }
+}
+
+impl Serialize for ObjectId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+fn parse_number_long(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_i64().or_else(|| n.as_f64().map(|f| f as i64)),
+        Value::String(s) => s.trim().parse().ok(),
+        Value::Object(map) => map
+            .get("$numberLong")
+            .or_else(|| map.get("$numberInt"))
+            .or_else(|| map.get("$numberDouble"))
+            .and_then(parse_number_long),
+        _ => None,
+    }
+}
+
+fn parse_date(value: &Value) -> Option<DateTime<Utc>> {
+    match value {
+        Value::String(s) => DateTime::parse_from_rfc3339(s)
+            .ok()
+            .map(|d| d.with_timezone(&Utc)),
+        Value::Object(map) => match map.get("$date") {
+            Some(inner) => parse_date(inner),
+            None => parse_number_long(value).and_then(|ms| Utc.timestamp_millis_opt(ms).single()),
+        },
+        Value::Number(_) => parse_number_long(value).and_then(|ms| Utc.timestamp_millis_opt(ms).single()),
+        _ => None,
+    }
+}
+
+/// `#[serde(deserialize_with = "extjson::date")]` for required `DateTime<Utc>` fields.
+pub fn date<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+    let value = Value::deserialize(deserializer)?;
+    parse_date(&value).ok_or_else(|| D::Error::custom(format!("invalid date: {}", value)))
+}
+
+/// Like [`date`], but `null` and missing fields become `None`.
+pub fn option_date<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<DateTime<Utc>>, D::Error> {
+    let value = Value::deserialize(deserializer)?;
+    if value.is_null() || value.as_str() == Some("") {
+        return Ok(None);
+    }
+    parse_date(&value)
+        .map(Some)
+        .ok_or_else(|| D::Error::custom(format!("invalid date: {}", value)))
+}
+
+/// Integer fields that may arrive as `{"$numberLong": "..."}` or `{"$numberInt": "..."}`.
+pub fn int<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i32, D::Error> {
+    let value = Value::deserialize(deserializer)?;
+    parse_number_long(&value)
+        .and_then(|n| i32::try_from(n).ok())
+        .ok_or_else(|| D::Error::custom(format!("invalid integer: {}", value)))
+}
+
+pub fn option_int<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<i32>, D::Error> {
+    let value = Value::deserialize(deserializer)?;
+    if value.is_null() {
+        return Ok(None);
+    }
+    parse_number_long(&value)
+        .and_then(|n| i32::try_from(n).ok())
+        .map(Some)
+        .ok_or_else(|| D::Error::custom(format!("invalid integer: {}", value)))
+}
+
+pub fn int_vec<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<i32>, D::Error> {
+    let values = Vec::<Value>::deserialize(deserializer)?;
+    values
+        .iter()
+        .map(|value| {
+            parse_number_long(value)
+                .and_then(|n| i32::try_from(n).ok())
+                .ok_or_else(|| D::Error::custom(format!("invalid integer: {}", value)))
+        })
+        .collect()
+}
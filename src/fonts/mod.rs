@@ -0,0 +1,77 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+// Bundled fonts. Font files in FONTS_DIR are made visible to LibreOffice
+// through a fontconfig file that adds the directory to the system
+// configuration, so deployments can ship fonts (e.g. a licensed Calibri or
+// extra Indic fonts) without rebuilding the image. LibreOffice embeds the
+// fonts it uses into the PDF.
+
+const FONT_EXTENSIONS: [&str; 4] = ["ttf", "otf", "ttc", "otc"];
+
+// fontconfig file written by `install`, passed to every LibreOffice run
+static FONTCONFIG_FILE: OnceLock<PathBuf> = OnceLock::new();
+
+fn is_font(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| FONT_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+// Registers the fonts in `dir` for LibreOffice. A missing or empty directory
+// leaves the system fonts alone.
+pub fn install(dir: &str) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return,
+        Err(e) => {
+            println!("Failed to read fonts directory {}: {}", dir, e);
+            return;
+        }
+    };
+    let fonts: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).filter(|p| is_font(p)).collect();
+    if fonts.is_empty() {
+        return;
+    }
+    let dir = match fs::canonicalize(dir) {
+        Ok(dir) => dir,
+        Err(e) => {
+            println!("Failed to resolve fonts directory {}: {}", dir, e);
+            return;
+        }
+    };
+
+    let config = format!(
+        concat!(
+            "<?xml version=\"1.0\"?>\n",
+            "<!DOCTYPE fontconfig SYSTEM \"fonts.dtd\">\n",
+            "<fontconfig>\n",
+            "  <include ignore_missing=\"yes\">/etc/fonts/fonts.conf</include>\n",
+            "  <dir>{}</dir>\n",
+            "</fontconfig>\n"
+        ),
+        escape(&dir.display().to_string())
+    );
+    let path = std::env::temp_dir().join(format!("dms-pdfmaker-fonts-{}.conf", std::process::id()));
+    if let Err(e) = fs::write(&path, config) {
+        println!("Failed to write fontconfig file {}: {}", path.display(), e);
+        return;
+    }
+
+    println!("Bundled {} font file(s) from {}", fonts.len(), dir.display());
+    let _ = FONTCONFIG_FILE.set(path);
+}
+
+// Points `command` at the bundled fonts, if any were installed
+pub fn configure(command: &mut Command) -> &mut Command {
+    if let Some(path) = FONTCONFIG_FILE.get() {
+        command.env("FONTCONFIG_FILE", path);
+    }
+    command
+}
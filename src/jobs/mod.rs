@@ -0,0 +1,264 @@
+use crate::batch::{self, BatchItem, BatchOptions};
+use crate::config::Config;
+use crate::render::{pdf_filename, render_pdf, RenderOptions};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tokio::sync::Semaphore;
+
+// Background rendering jobs for work that would outlive an HTTP request.
+// Job state is kept in memory; artifacts are written to `output/jobs/` and
+// removed together with the job once it is older than the configured TTL.
+
+pub const JOBS_DIR: &str = "output/jobs";
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Done,
+    Failed,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Job {
+    pub id: String,
+    pub status: JobStatus,
+    pub total: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip)]
+    pub result_path: Option<PathBuf>,
+    #[serde(skip)]
+    pub content_type: &'static str,
+    #[serde(skip)]
+    pub filename: String,
+}
+
+impl Job {
+    pub fn progress(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        (self.completed + self.failed) as f64 / self.total as f64
+    }
+}
+
+// What a job renders: a single proposal (PDF result) or a batch (ZIP).
+pub enum JobInput {
+    Single(Box<crate::models::Submission>),
+    Batch(Vec<BatchItem>),
+}
+
+#[derive(Clone)]
+pub struct JobStore {
+    jobs: Arc<Mutex<HashMap<String, Job>>>,
+    // Limits how many jobs render at the same time; the rest stay queued
+    slots: Arc<Semaphore>,
+}
+
+impl JobStore {
+    pub fn new(concurrency: usize) -> Self {
+        Self {
+            jobs: Arc::new(Mutex::new(HashMap::new())),
+            slots: Arc::new(Semaphore::new(concurrency.max(1))),
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<Job> {
+        self.jobs.lock().unwrap().get(id).cloned()
+    }
+
+    fn update(&self, id: &str, f: impl FnOnce(&mut Job)) {
+        if let Some(job) = self.jobs.lock().unwrap().get_mut(id) {
+            f(job);
+        }
+    }
+
+    // Registers the job and starts it in the background. Returns the job as
+    // initially queued.
+    pub fn submit(
+        &self,
+        input: JobInput,
+        config: Arc<Config>,
+        options: RenderOptions,
+        batch_options: BatchOptions,
+    ) -> Job {
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        let (total, content_type, filename) = match &input {
+            JobInput::Single(submission) => (1, "application/pdf", pdf_filename(submission)),
+            JobInput::Batch(items) => (items.len(), "application/zip", format!("proposals_{}.zip", id)),
+        };
+
+        let job = Job {
+            id: id.clone(),
+            status: JobStatus::Queued,
+            total,
+            completed: 0,
+            failed: 0,
+            created_at: Utc::now(),
+            finished_at: None,
+            error: None,
+            result_path: None,
+            content_type,
+            filename,
+        };
+        self.jobs.lock().unwrap().insert(id.clone(), job.clone());
+
+        let store = self.clone();
+        tokio::spawn(async move {
+            let Ok(_slot) = store.slots.clone().acquire_owned().await else {
+                return;
+            };
+            store.update(&id, |job| job.status = JobStatus::Running);
+            println!("Starting job {}", id);
+
+            let result = match input {
+                JobInput::Single(submission) => store.run_single(&id, *submission, config, options, batch_options).await,
+                JobInput::Batch(items) => store.run_batch(&id, items, config, options, batch_options).await,
+            };
+
+            store.update(&id, |job| {
+                job.finished_at = Some(Utc::now());
+                match result {
+                    Ok(path) => {
+                        job.status = JobStatus::Done;
+                        job.result_path = Some(path);
+                    }
+                    Err(e) => {
+                        println!("Job {} failed: {}", job.id, e);
+                        job.status = JobStatus::Failed;
+                        job.error = Some(e);
+                    }
+                }
+            });
+        });
+
+        job
+    }
+
+    async fn run_single(
+        &self,
+        id: &str,
+        submission: crate::models::Submission,
+        config: Arc<Config>,
+        options: RenderOptions,
+        batch_options: BatchOptions,
+    ) -> Result<PathBuf, String> {
+        if submission.is_discarded() && !batch_options.force {
+            self.update(id, |job| job.failed = 1);
+            return Err("Submission has been discarded".to_string());
+        }
+
+        let path = Path::new(JOBS_DIR).join(format!("{}.pdf", id));
+        let rendered = tokio::task::spawn_blocking(move || {
+            let pdf = render_pdf(&submission, &config, &options).map_err(|e| e.message().to_string())?;
+            fs::create_dir_all(JOBS_DIR).map_err(|e| e.to_string())?;
+            fs::write(&path, pdf).map_err(|e| e.to_string())?;
+            Ok::<_, String>(path)
+        })
+        .await
+        .map_err(|e| format!("Render task failed: {}", e))?;
+
+        self.update(id, |job| match rendered {
+            Ok(_) => job.completed = 1,
+            Err(_) => job.failed = 1,
+        });
+        rendered
+    }
+
+    async fn run_batch(
+        &self,
+        id: &str,
+        items: Vec<BatchItem>,
+        config: Arc<Config>,
+        options: RenderOptions,
+        batch_options: BatchOptions,
+    ) -> Result<PathBuf, String> {
+        let total = items.len();
+        let outcomes = batch::spawn_renders(items, config, options, batch_options);
+        let path = Path::new(JOBS_DIR).join(format!("{}.zip", id));
+
+        let store = self.clone();
+        let job_id = id.to_string();
+        tokio::task::spawn_blocking(move || {
+            fs::create_dir_all(JOBS_DIR).map_err(|e| e.to_string())?;
+            let file = fs::File::create(&path).map_err(|e| e.to_string())?;
+            batch::write_zip(file, total, outcomes, |outcome| {
+                let rendered = outcome.is_rendered();
+                store.update(&job_id, |job| {
+                    if rendered {
+                        job.completed += 1;
+                    } else {
+                        job.failed += 1;
+                    }
+                });
+            })
+            .map_err(|e| e.to_string())?;
+            Ok(path)
+        })
+        .await
+        .map_err(|e| format!("Render task failed: {}", e))?
+    }
+
+    // Forgets finished jobs older than `ttl` and deletes their artifacts,
+    // along with any stale PDFs left in `output/`.
+    pub fn cleanup(&self, ttl: Duration) {
+        let cutoff = Utc::now() - ttl;
+        let mut expired = Vec::new();
+        self.jobs.lock().unwrap().retain(|_, job| {
+            let keep = job.finished_at.is_none_or(|finished| finished > cutoff);
+            if !keep {
+                expired.push(job.id.clone());
+                if let Some(path) = &job.result_path {
+                    let _ = fs::remove_file(path);
+                }
+            }
+            keep
+        });
+        if !expired.is_empty() {
+            println!("Expired {} job(s)", expired.len());
+        }
+
+        let max_age = ttl.to_std().unwrap_or_default();
+        for dir in ["output", JOBS_DIR] {
+            remove_files_older_than(Path::new(dir), max_age);
+        }
+    }
+}
+
+fn remove_files_older_than(dir: &Path, max_age: std::time::Duration) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let now = SystemTime::now();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if !metadata.is_file() {
+            continue;
+        }
+        let age = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok());
+        if age.is_some_and(|age| age > max_age) {
+            match fs::remove_file(&path) {
+                Ok(_) => println!("Removed expired file: {}", path.display()),
+                Err(e) => println!("Error removing {}: {}", path.display(), e),
+            }
+        }
+    }
+}
@@ -1,4 +1,28 @@
-mod models;
-mod page1;
-mod page2;
-mod utils;
+pub mod batch;
+pub mod branding;
+pub mod budget;
+pub mod cache;
+pub mod config;
+pub mod decorations;
+pub mod diff;
+pub mod document;
+pub mod dms;
+pub mod evaluation;
+pub mod extjson;
+pub mod fonts;
+pub mod jobs;
+pub mod metadata;
+pub mod models;
+pub mod page1;
+pub mod page2;
+pub mod pdfa;
+pub mod progress;
+pub mod qr;
+pub mod render;
+pub mod sanction;
+pub mod sheet;
+pub mod signing;
+pub mod store;
+pub mod summary;
+pub mod thumbnail;
+pub mod utils;
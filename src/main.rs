@@ -1,145 +1,1254 @@
 use axum::{
     self,
-    body::Body,
-    extract::Json,
-    http::{header, StatusCode},
+    body::{Body, Bytes},
+    extract::{DefaultBodyLimit, Json, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
     response::Response,
     routing::{get, post},
     Router,
 };
-use docx_rs::{Docx, Paragraph};
+use batch::BatchOptions;
+use budget::BudgetFormat;
+use cache::RenderCache;
+use config::Config;
+use dms::{DmsClient, DmsError, SubmissionQuery};
+use jobs::{JobInput, JobStatus, JobStore};
 use models::*;
-use page1::page1_content;
-use page2::{page2_content_signatures, page2_content_with_table};
-use reqwest;
-use std::fs;
+use progress::{Expenditure, ReportKind, ReportRequest};
+use render::{convert_to_pdf, pack_docx, pdf_filename, render_pdf, DocumentFormat, RenderOptions};
+use sanction::{Approval, SanctionRequest};
+use serde::Deserialize;
 use std::sync::Arc;
-use chrono;
+use store::SubmissionStore;
+use thumbnail::ThumbnailFormat;
 use tower_http::cors::{Any, CorsLayer}; // Import CorsLayer
 
 
+mod batch;
+mod branding;
+mod budget;
+mod cache;
+mod config;
+mod decorations;
+mod diff;
+mod document;
+mod dms;
+mod evaluation;
+mod extjson;
+mod fonts;
+mod jobs;
+mod metadata;
 mod models;
 mod page1;
 mod page2;
+mod pdfa;
+mod progress;
+mod qr;
+mod render;
+mod sanction;
+mod sheet;
+mod signing;
+mod store;
+mod summary;
+mod thumbnail;
 mod utils;
 
+#[derive(Clone)]
+struct AppState {
+    config: Arc<Config>,
+    jobs: JobStore,
+    dms: Arc<DmsClient>,
+    store: SubmissionStore,
+    cache: RenderCache,
+}
 
+#[derive(Deserialize)]
+struct DownloadOptions {
+    // Render even if the submission has been discarded
+    #[serde(default)]
+    force: bool,
+}
 
 // New function for the POST endpoint that accepts a JSON submission
-async fn generate_document_from_json(Json(submission): Json<Submission>) -> Response<Body> {
-    generate_pdf(&submission)
+async fn generate_document_from_json(
+    State(state): State<AppState>,
+    Query(options): Query<DownloadOptions>,
+    Query(render_options): Query<RenderOptions>,
+    headers: HeaderMap,
+    Json(raw): Json<serde_json::Value>,
+) -> Response<Body> {
+    // parsed from the raw JSON so the version history keeps the payload as sent
+    let submission = match Submission::deserialize(&raw) {
+        Ok(submission) => submission,
+        Err(e) => {
+            return Response::builder()
+                .status(StatusCode::UNPROCESSABLE_ENTITY)
+                .body(Body::from(format!("Invalid submission: {}", e)))
+                .unwrap();
+        }
+    };
+    if submission.is_discarded() && !options.force {
+        return discarded_response(&submission);
+    }
+
+    let version = record_version(&state.store, &submission, &raw, "download");
+    render_in_background(&state, submission, render_options, version, if_none_match(&headers)).await
+}
+
+fn discarded_response(submission: &Submission) -> Response<Body> {
+    println!("Refusing to render discarded submission: {}", submission.unique_id);
+    Response::builder()
+        .status(StatusCode::CONFLICT)
+        .body(Body::from("Submission has been discarded; pass ?force=true to render it anyway"))
+        .unwrap()
+}
+
+// Stores the payload in the version history; store errors are only logged
+// so they never block rendering.
+fn record_version(
+    store: &SubmissionStore,
+    submission: &Submission,
+    raw: &serde_json::Value,
+    source: &str,
+) -> Option<u32> {
+    match store.record(submission, raw, source) {
+        Ok(version) => Some(version),
+        Err(e) => {
+            println!("Error storing {}: {}", submission.unique_id, e);
+            None
+        }
+    }
+}
+
+// Tells the client which stored version a PDF was generated from
+fn with_version(mut response: Response<Body>, version: Option<u32>) -> Response<Body> {
+    if let Some(version) = version {
+        response
+            .headers_mut()
+            .insert("X-Submission-Version", header::HeaderValue::from(version));
+    }
+    response
+}
+
+fn if_none_match(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(|tag| tag.trim().trim_start_matches("W/"))
+        .any(|tag| tag == "*" || tag == etag)
 }
 
-// Helper function to generate PDF from a submission
-fn generate_pdf(submission: &Submission) -> Response<Body> {
-    let submission_id = &submission.unique_id;
-    
-    let docx_path = format!("docx_file_{}.docx", submission_id);
-    let pdf_filename = format!("proposal_{}.pdf", submission_id);
-    let pdf_path = format!("output/{}", pdf_filename);
+#[derive(Deserialize)]
+struct PreviewOptions {
+    // "html" (default) or "json" for the document structure itself
+    format: Option<String>,
+}
+
+// HTML preview of the proposal, laid out like the PDF but rendered without
+// LibreOffice so it is fast enough to refresh while editing
+async fn generate_preview(
+    State(state): State<AppState>,
+    Query(preview_options): Query<PreviewOptions>,
+    Query(render_options): Query<RenderOptions>,
+    Json(submission): Json<Submission>,
+) -> Response<Body> {
+    let contents = render_options
+        .contents
+        .unwrap_or(state.config.template.table_of_contents);
+    let qr = render_options.qr.unwrap_or(state.config.template.cover_qr);
+    let styles = match render::style_sheet(&state.config, &render_options) {
+        Ok(styles) => styles,
+        Err(e) => return render_error_response(&e),
+    };
+    let proposal = document::proposal(&submission, &state.config.template, contents, qr, styles);
+    if preview_options.format.as_deref().is_some_and(|f| f.eq_ignore_ascii_case("json")) {
+        return json_response(StatusCode::OK, &proposal);
+    }
+
+    let watermark = decorations::resolve_watermark(
+        &submission,
+        &state.config.template,
+        render_options.watermark.as_deref(),
+    );
+    let html = document::html::to_html(&proposal, watermark.as_deref());
 
-    // Ensure output directory exists
-    if let Err(e) = fs::create_dir_all("output") {
-        println!("Error creating output directory: {}", e);
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+        .body(Body::from(html))
+        .unwrap()
+}
+
+// Helper function to generate PDF from a submission. Renders are cached by
+// content hash, which is also sent as the ETag so unchanged submissions can
+// be revalidated with If-None-Match.
+fn generate_pdf(
+    submission: &Submission,
+    config: &Config,
+    cache: &RenderCache,
+    options: &RenderOptions,
+    if_none_match: Option<&str>,
+) -> Response<Body> {
+    let key = cache::render_key(submission, config, options);
+    let etag = format!("\"{}\"", key);
+
+    if if_none_match.is_some_and(|value| etag_matches(value, &etag)) {
+        println!("Not modified: {}", submission.unique_id);
         return Response::builder()
-            .status(StatusCode::INTERNAL_SERVER_ERROR)
-            .body(Body::from("Failed to create output directory"))
+            .status(StatusCode::NOT_MODIFIED)
+            .header(header::ETAG, etag)
+            .body(Body::empty())
             .unwrap();
     }
 
-    // create the Word document
-    let file = match std::fs::File::create(&docx_path) {
-        Ok(file) => file,
+    let (rendered, cache_status) = cached_pdf(submission, config, cache, options, &key);
+    match rendered {
+        Ok(pdf_content) => Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/pdf")
+            .header(
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", pdf_filename(submission)),
+            )
+            .header(header::ETAG, etag)
+            .header(header::CACHE_CONTROL, "private, no-cache")
+            .header("X-Cache", cache_status)
+            .body(Body::from(pdf_content))
+            .unwrap(),
         Err(e) => {
-            println!("Error creating DOCX file: {}", e);
-            return Response::builder()
+            println!("Error rendering {}: {}", submission.unique_id, e);
+            render_error_response(&e)
+        }
+    }
+}
+
+// Unknown branding profiles are the client's mistake and signing without a
+// certificate a deployment one; anything else is ours
+fn render_error_response(e: &render::RenderError) -> Response<Body> {
+    let status = match e {
+        render::RenderError::UnknownBranding(_) => StatusCode::BAD_REQUEST,
+        render::RenderError::SigningUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    Response::builder()
+        .status(status)
+        .body(Body::from(e.message()))
+        .unwrap()
+}
+
+// The PDF for `key` from the cache, rendering and caching it on a miss.
+// Also returns "HIT" or "MISS" for the X-Cache header.
+fn cached_pdf(
+    submission: &Submission,
+    config: &Config,
+    cache: &RenderCache,
+    options: &RenderOptions,
+    key: &str,
+) -> (Result<Vec<u8>, render::RenderError>, &'static str) {
+    match cache.get(key) {
+        Some(pdf) => {
+            println!("Serving cached PDF for {}", submission.unique_id);
+            (Ok(pdf), "HIT")
+        }
+        None => {
+            let rendered = render_pdf(submission, config, options);
+            if let Ok(pdf) = &rendered {
+                cache.put(key, pdf);
+            }
+            (rendered, "MISS")
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ThumbnailOptions {
+    width: Option<u32>,
+    #[serde(default)]
+    format: ThumbnailFormat,
+}
+
+// Cover page image for dashboard cards, rasterized from the same (cached)
+// PDF that /pdf returns
+async fn generate_thumbnail(
+    State(state): State<AppState>,
+    Path(unique_id): Path<String>,
+    Query(options): Query<DownloadOptions>,
+    Query(thumbnail_options): Query<ThumbnailOptions>,
+    Query(render_options): Query<RenderOptions>,
+    headers: HeaderMap,
+) -> Response<Body> {
+    let width = thumbnail_options.width.unwrap_or(thumbnail::DEFAULT_WIDTH);
+    if !(thumbnail::MIN_WIDTH..=thumbnail::MAX_WIDTH).contains(&width) {
+        return Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .body(Body::from(format!(
+                "width must be between {} and {}",
+                thumbnail::MIN_WIDTH,
+                thumbnail::MAX_WIDTH
+            )))
+            .unwrap();
+    }
+    let format = thumbnail_options.format;
+    if let Err(e) = render::style_sheet(&state.config, &render_options) {
+        return render_error_response(&e);
+    }
+
+    let (submission, raw) = match state.dms.fetch_submission_json(&unique_id).await {
+        Ok(fetched) => fetched,
+        Err(e) => return dms_error_response(e),
+    };
+    if submission.is_discarded() && !options.force {
+        return discarded_response(&submission);
+    }
+    record_version(&state.store, &submission, &raw, "dms");
+
+    let key = cache::render_key(&submission, &state.config, &render_options);
+    let name = RenderCache::thumbnail_name(&key, width, format.extension());
+    let etag = format!("\"{}\"", name);
+    if if_none_match(&headers).is_some_and(|value| etag_matches(&value, &etag)) {
+        return Response::builder()
+            .status(StatusCode::NOT_MODIFIED)
+            .header(header::ETAG, etag)
+            .body(Body::empty())
+            .unwrap();
+    }
+
+    let config = state.config.clone();
+    let cache = state.cache.clone();
+    let rendered = tokio::task::spawn_blocking(move || -> Result<_, &'static str> {
+        if let Some(image) = cache.read(&name) {
+            return Ok((image, "HIT"));
+        }
+        let pdf = cached_pdf(&submission, &config, &cache, &render_options, &key)
+            .0
+            .map_err(|e| {
+                println!("Error rendering {}: {}", submission.unique_id, e);
+                e.message()
+            })?;
+        let image = thumbnail::cover_thumbnail(&pdf, width, format).map_err(|e| {
+            println!("Error creating thumbnail for {}: {}", submission.unique_id, e);
+            e.message()
+        })?;
+        cache.write(&name, &image);
+        Ok((image, "MISS"))
+    })
+    .await;
+
+    match rendered {
+        Ok(Ok((image, cache_status))) => Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, format.content_type())
+            .header(header::ETAG, etag)
+            .header(header::CACHE_CONTROL, "private, no-cache")
+            .header("X-Cache", cache_status)
+            .body(Body::from(image))
+            .unwrap(),
+        Ok(Err(message)) => Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .body(Body::from(message))
+            .unwrap(),
+        Err(e) => {
+            println!("Thumbnail task failed: {}", e);
+            Response::builder()
                 .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .body(Body::from("Failed to create DOCX file"))
+                .body(Body::from("Failed to create thumbnail"))
+                .unwrap()
+        }
+    }
+}
+
+// Renders many submissions at once and streams back a ZIP of PDFs
+async fn generate_batch(
+    State(state): State<AppState>,
+    Query(options): Query<BatchOptions>,
+    Query(render_options): Query<RenderOptions>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response<Body> {
+    let ndjson = is_ndjson(&headers);
+
+    let items = match batch::parse_submissions(&body, ndjson) {
+        Ok(items) if !items.is_empty() => items,
+        Ok(_) => {
+            return Response::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .body(Body::from("No submissions in request body"))
                 .unwrap();
         }
+        Err(e) => {
+            println!("Error parsing batch request: {}", e);
+            return Response::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .body(Body::from(e))
+                .unwrap();
+        }
+    };
+
+    record_items(&state.store, &items, "batch");
+    println!("Rendering batch of {} submissions", items.len());
+    let body = batch::render_zip(items, state.config.clone(), render_options, options);
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/zip")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"proposals_{}.zip\"", chrono::Utc::now().format("%Y%m%d_%H%M%S")),
+        )
+        .body(body)
+        .unwrap()
+}
+
+// Lists submissions from the DMS, filtered and paginated. The total number
+// of matches is returned in X-Total-Count.
+async fn list_submissions(
+    State(state): State<AppState>,
+    Query(query): Query<SubmissionQuery>,
+) -> Response<Body> {
+    let submissions = match state.dms.list_submissions(&query).await {
+        Ok(submissions) => submissions,
+        Err(e) => return dms_error_response(e),
+    };
+
+    let (page, total) = query.apply(submissions);
+    println!("Listing {} of {} submission(s)", page.len(), total);
+
+    let mut response = SubmissionResponse::to_json_response(page);
+    let headers = response.headers_mut();
+    headers.insert("X-Total-Count", header::HeaderValue::from(total));
+    headers.insert("X-Page", header::HeaderValue::from(query.page()));
+    headers.insert("X-Per-Page", header::HeaderValue::from(query.limit()));
+    response
+}
+
+// Fetches the submission from the DMS by unique_id and renders it
+async fn generate_document_from_dms(
+    State(state): State<AppState>,
+    Path(unique_id): Path<String>,
+    Query(options): Query<DownloadOptions>,
+    Query(render_options): Query<RenderOptions>,
+    headers: HeaderMap,
+) -> Response<Body> {
+    let (submission, raw) = match state.dms.fetch_submission_json(&unique_id).await {
+        Ok(fetched) => fetched,
+        Err(e) => return dms_error_response(e),
+    };
+
+    if submission.is_discarded() && !options.force {
+        return discarded_response(&submission);
+    }
+
+    let version = record_version(&state.store, &submission, &raw, "dms");
+    render_in_background(&state, submission, render_options, version, if_none_match(&headers)).await
+}
+
+async fn render_in_background(
+    state: &AppState,
+    submission: Submission,
+    options: RenderOptions,
+    version: Option<u32>,
+    if_none_match: Option<String>,
+) -> Response<Body> {
+    let config = state.config.clone();
+    let cache = state.cache.clone();
+    tokio::task::spawn_blocking(move || {
+        let response = generate_pdf(&submission, &config, &cache, &options, if_none_match.as_deref());
+        with_version(response, version)
+    })
+    .await
+        .unwrap_or_else(|e| {
+            println!("Render task failed: {}", e);
+            Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Body::from("Failed to generate PDF"))
+                .unwrap()
+        })
+}
+
+fn record_items(store: &SubmissionStore, items: &[batch::BatchItem], source: &str) {
+    for item in items {
+        if let (Ok(submission), Some(raw)) = (&item.submission, &item.raw) {
+            record_version(store, submission, raw, source);
+        }
+    }
+}
+
+fn store_error_response(error: store::StoreError) -> Response<Body> {
+    println!("{}", error);
+    Response::builder()
+        .status(StatusCode::INTERNAL_SERVER_ERROR)
+        .body(Body::from("Failed to read submission store"))
+        .unwrap()
+}
+
+#[derive(Deserialize)]
+struct DiffOptions {
+    // Defaults to the first stored version
+    from: Option<u32>,
+    // Defaults to the latest stored version
+    to: Option<u32>,
+    #[serde(default)]
+    format: DocumentFormat,
+}
+
+// Redline report of what changed between two stored versions
+async fn generate_diff(
+    State(state): State<AppState>,
+    Path(unique_id): Path<String>,
+    Query(options): Query<DiffOptions>,
+    Query(render_options): Query<RenderOptions>,
+) -> Response<Body> {
+    let styles = match render::style_sheet(&state.config, &render_options) {
+        Ok(styles) => styles,
+        Err(e) => return render_error_response(&e),
+    };
+    let versions = match state.store.versions(&unique_id) {
+        Ok(versions) => versions,
+        Err(e) => return store_error_response(e),
+    };
+    let (Some(first), Some(last)) = (versions.first(), versions.last()) else {
+        return Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::from("No stored versions for this submission"))
+            .unwrap();
     };
-    
-    let mut doc = Docx::new();
+    let from = options.from.unwrap_or(first.version);
+    let to = options.to.unwrap_or(last.version);
 
-    // page 1 content
-    for paragraph in page1_content(submission) {
-        doc = doc.add_paragraph(paragraph);
+    let load = |version: u32| -> Result<Option<_>, store::StoreError> {
+        match state.store.version(&unique_id, version)? {
+            Some(stored) => {
+                let submission = stored.submission()?;
+                Ok(Some((stored, submission)))
+            }
+            None => Ok(None),
+        }
+    };
+    let (old_stored, old) = match load(from) {
+        Ok(Some(loaded)) => loaded,
+        Ok(None) => return version_not_found(),
+        Err(e) => return store_error_response(e),
+    };
+    let (new_stored, new) = match load(to) {
+        Ok(Some(loaded)) => loaded,
+        Ok(None) => return version_not_found(),
+        Err(e) => return store_error_response(e),
+    };
+
+    let report = diff::diff_document(
+        &unique_id,
+        &diff::DiffSide { version: from, stored_at: old_stored.stored_at, submission: &old },
+        &diff::DiffSide { version: to, stored_at: new_stored.stored_at, submission: &new },
+        styles,
+    );
+
+    if options.format == DocumentFormat::Html {
+        return html_response(&state, &render_options, |_| report);
     }
+    let filename = diff::diff_filename(&unique_id, from, to, options.format.extension());
+    document_file(&state, options.format, filename, "diff", render_options, None, move |_, _| {
+        Ok(document::docx::to_docx(&report).build())
+    })
+    .await
+}
 
-    // page break
-    doc = doc.add_paragraph(Paragraph::new().page_break_before(true));
+fn attachment(content_type: &str, filename: &str, content: Vec<u8>) -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", filename),
+        )
+        .body(Body::from(content))
+        .unwrap()
+}
 
-    // page 2 content
-    let (paragraphs, table) = page2_content_with_table(submission);
-    for paragraph in paragraphs {
-        doc = doc.add_paragraph(paragraph);
+#[derive(Deserialize)]
+struct EvaluationOptions {
+    #[serde(default)]
+    format: DocumentFormat,
+}
+
+// Reviewer evaluation form for the submission in the requested format
+async fn render_evaluation(
+    state: &AppState,
+    submission: Submission,
+    format: DocumentFormat,
+    render_options: RenderOptions,
+) -> Response<Body> {
+    if format == DocumentFormat::Html {
+        return html_response(state, &render_options, |styles| {
+            evaluation::evaluation_form(&submission, &state.config.evaluation, styles)
+        });
     }
-    doc = doc.add_table(table);
-    for paragraph in page2_content_signatures() {
-        doc = doc.add_paragraph(paragraph);
+    let filename = evaluation::evaluation_filename(&submission, format.extension());
+    document_file(state, format, filename, "evaluation form", render_options, None, move |config, options| {
+        render::build_evaluation_docx(&submission, config, options)
+    })
+    .await
+}
+
+async fn generate_evaluation_from_json(
+    State(state): State<AppState>,
+    Query(options): Query<DownloadOptions>,
+    Query(evaluation_options): Query<EvaluationOptions>,
+    Query(render_options): Query<RenderOptions>,
+    Json(submission): Json<Submission>,
+) -> Response<Body> {
+    if submission.is_discarded() && !options.force {
+        return discarded_response(&submission);
     }
+    render_evaluation(&state, submission, evaluation_options.format, render_options).await
+}
 
-    if let Err(e) = doc.build().pack(file) {
-        println!("Error packing DOCX: {}", e);
+async fn generate_evaluation_from_dms(
+    State(state): State<AppState>,
+    Path(unique_id): Path<String>,
+    Query(options): Query<DownloadOptions>,
+    Query(evaluation_options): Query<EvaluationOptions>,
+    Query(render_options): Query<RenderOptions>,
+) -> Response<Body> {
+    let submission = match state.dms.fetch_submission(&unique_id).await {
+        Ok(submission) => submission,
+        Err(e) => return dms_error_response(e),
+    };
+    if submission.is_discarded() && !options.force {
+        return discarded_response(&submission);
+    }
+    render_evaluation(&state, submission, evaluation_options.format, render_options).await
+}
+
+#[derive(Deserialize)]
+struct LetterOptions {
+    #[serde(default)]
+    format: DocumentFormat,
+}
+
+// Sanction letters are only issued for approved proposals unless forced
+fn not_approved_response(submission: &Submission) -> Response<Body> {
+    println!("Refusing sanction letter for {} submission: {}", submission.status, submission.unique_id);
+    Response::builder()
+        .status(StatusCode::CONFLICT)
+        .body(Body::from(format!(
+            "Submission is {}, not approved; pass ?force=true to generate the letter anyway",
+            submission.status
+        )))
+        .unwrap()
+}
+
+// Sanction letter for the approved submission in the requested format. The
+// PDF is signed like proposals when signing is enabled.
+async fn render_sanction_letter(
+    state: &AppState,
+    submission: Submission,
+    approval: Approval,
+    format: DocumentFormat,
+    render_options: RenderOptions,
+) -> Response<Body> {
+    if let Err(e) = approval.validate() {
         return Response::builder()
-            .status(StatusCode::INTERNAL_SERVER_ERROR)
-            .body(Body::from("Failed to create DOCX document"))
+            .status(StatusCode::BAD_REQUEST)
+            .body(Body::from(format!("Invalid approval: {}", e)))
+            .unwrap();
+    }
+
+    if format == DocumentFormat::Html {
+        return html_response(state, &render_options, |styles| {
+            sanction::sanction_letter(&submission, &approval, &state.config.sanction, styles)
+        });
+    }
+    let filename = sanction::letter_filename(&submission, format.extension());
+    let unique_id = submission.unique_id.clone();
+    document_file(state, format, filename, "sanction letter", render_options, Some(unique_id), move |config, options| {
+        render::build_sanction_docx(&submission, &approval, config, options)
+    })
+    .await
+}
+
+// The HTML rendition of a document, built with the request's style sheet
+fn html_response(
+    state: &AppState,
+    render_options: &RenderOptions,
+    build: impl FnOnce(&document::StyleSheet) -> document::Document,
+) -> Response<Body> {
+    let styles = match render::style_sheet(&state.config, render_options) {
+        Ok(styles) => styles,
+        Err(e) => return render_error_response(&e),
+    };
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, DocumentFormat::Html.content_type())
+        .body(Body::from(document::html::to_html(&build(styles), None)))
+        .unwrap()
+}
+
+// Returns the document built by `build` as DOCX or PDF, rendered off the
+// async runtime. Documents issued for a submission (`signed_as` its
+// unique_id) follow ?pdfa and are signed like proposals when signing is
+// enabled. `what` names the document in messages.
+async fn document_file(
+    state: &AppState,
+    format: DocumentFormat,
+    filename: String,
+    what: &str,
+    render_options: RenderOptions,
+    signed_as: Option<String>,
+    build: impl FnOnce(&Config, &RenderOptions) -> Result<docx_rs::XMLDocx, render::RenderError> + Send + 'static,
+) -> Response<Body> {
+    let docx = format == DocumentFormat::Docx;
+    let sign = signed_as.is_some() && !docx && render::signing_enabled(&state.config, &render_options);
+    if sign && state.config.signing.signer.is_none() {
+        return render_error_response(&render::RenderError::SigningUnavailable);
+    }
+
+    let rendered = {
+        let config = state.config.clone();
+        let filename = filename.clone();
+        tokio::task::spawn_blocking(move || {
+            let xml = build(&config, &render_options)?;
+            if docx {
+                return pack_docx(xml);
+            }
+            let pdfa = signed_as.is_some() && render_options.pdfa.unwrap_or(config.template.pdfa);
+            let pdf = convert_to_pdf(xml, &filename, pdfa)?;
+            match signed_as {
+                Some(unique_id) if sign => render::sign_pdf(pdf, &filename, &unique_id, &config),
+                _ => Ok(pdf),
+            }
+        })
+        .await
+    };
+    match rendered {
+        Ok(Ok(content)) => attachment(format.content_type(), &filename, content),
+        Ok(Err(e)) => {
+            println!("Error rendering {} {}: {}", what, filename, e);
+            render_error_response(&e)
+        }
+        Err(e) => {
+            println!("Render task failed: {}", e);
+            Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Body::from(format!("Failed to generate {}", what)))
+                .unwrap()
+        }
+    }
+}
+
+async fn generate_sanction_letter_from_json(
+    State(state): State<AppState>,
+    Query(options): Query<DownloadOptions>,
+    Query(letter_options): Query<LetterOptions>,
+    Query(render_options): Query<RenderOptions>,
+    Json(request): Json<SanctionRequest>,
+) -> Response<Body> {
+    if request.submission.is_discarded() && !options.force {
+        return discarded_response(&request.submission);
+    }
+    if request.submission.status != SubmissionStatus::Approved && !options.force {
+        return not_approved_response(&request.submission);
+    }
+    render_sanction_letter(&state, request.submission, request.approval, letter_options.format, render_options).await
+}
+
+// Sanction letter for a submission in the DMS; the body is the approval
+async fn generate_sanction_letter_from_dms(
+    State(state): State<AppState>,
+    Path(unique_id): Path<String>,
+    Query(options): Query<DownloadOptions>,
+    Query(letter_options): Query<LetterOptions>,
+    Query(render_options): Query<RenderOptions>,
+    Json(approval): Json<Approval>,
+) -> Response<Body> {
+    let submission = match state.dms.fetch_submission(&unique_id).await {
+        Ok(submission) => submission,
+        Err(e) => return dms_error_response(e),
+    };
+    if submission.is_discarded() && !options.force {
+        return discarded_response(&submission);
+    }
+    if submission.status != SubmissionStatus::Approved && !options.force {
+        return not_approved_response(&submission);
+    }
+    render_sanction_letter(&state, submission, approval, letter_options.format, render_options).await
+}
+
+#[derive(Deserialize)]
+struct ReportOptions {
+    #[serde(default)]
+    format: DocumentFormat,
+}
+
+// Progress report or utilisation certificate in the requested format
+async fn render_report(
+    state: &AppState,
+    kind: ReportKind,
+    submission: Submission,
+    expenditure: Expenditure,
+    format: DocumentFormat,
+    render_options: RenderOptions,
+) -> Response<Body> {
+    if let Err(e) = expenditure.validate() {
+        return Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .body(Body::from(format!("Invalid expenditure: {}", e)))
             .unwrap();
     }
-    
-    println!("Successfully created DOCX file: {}", docx_path);
-
-    // convert to PDF
-    let response = match utils::convert_docx_to_pdf(&docx_path, "output", Some(&pdf_filename)) {
-        Ok(_) => match fs::read(&pdf_path) {
-            Ok(pdf_content) => {
-                let response = Response::builder()
-                    .status(StatusCode::OK)
-                    .header(header::CONTENT_TYPE, "application/pdf")
-                    .header(
-                        header::CONTENT_DISPOSITION,
-                        format!("attachment; filename=\"{}\"", pdf_filename),
-                    )
-                    .body(Body::from(pdf_content))
+
+    if format == DocumentFormat::Html {
+        return html_response(state, &render_options, |styles| {
+            progress::report(kind, &submission, &expenditure, styles)
+        });
+    }
+    let filename = kind.filename(&submission, format.extension());
+    let unique_id = submission.unique_id.clone();
+    let what = kind.title().to_lowercase();
+    document_file(state, format, filename, &what, render_options, Some(unique_id), move |config, options| {
+        render::build_report_docx(kind, &submission, &expenditure, config, options)
+    })
+    .await
+}
+
+async fn generate_progress_report_from_json(
+    State(state): State<AppState>,
+    Query(options): Query<DownloadOptions>,
+    Query(report_options): Query<ReportOptions>,
+    Query(render_options): Query<RenderOptions>,
+    Json(request): Json<ReportRequest>,
+) -> Response<Body> {
+    if request.submission.is_discarded() && !options.force {
+        return discarded_response(&request.submission);
+    }
+    let format = report_options.format;
+    render_report(&state, ReportKind::Progress, request.submission, request.expenditure, format, render_options).await
+}
+
+async fn generate_utilisation_certificate_from_json(
+    State(state): State<AppState>,
+    Query(options): Query<DownloadOptions>,
+    Query(report_options): Query<ReportOptions>,
+    Query(render_options): Query<RenderOptions>,
+    Json(request): Json<ReportRequest>,
+) -> Response<Body> {
+    if request.submission.is_discarded() && !options.force {
+        return discarded_response(&request.submission);
+    }
+    let format = report_options.format;
+    render_report(&state, ReportKind::Utilisation, request.submission, request.expenditure, format, render_options).await
+}
+
+// Reports for a submission in the DMS; the body is the expenditure
+async fn generate_progress_report_from_dms(
+    State(state): State<AppState>,
+    Path(unique_id): Path<String>,
+    Query(options): Query<DownloadOptions>,
+    Query(report_options): Query<ReportOptions>,
+    Query(render_options): Query<RenderOptions>,
+    Json(expenditure): Json<Expenditure>,
+) -> Response<Body> {
+    let submission = match state.dms.fetch_submission(&unique_id).await {
+        Ok(submission) => submission,
+        Err(e) => return dms_error_response(e),
+    };
+    if submission.is_discarded() && !options.force {
+        return discarded_response(&submission);
+    }
+    let format = report_options.format;
+    render_report(&state, ReportKind::Progress, submission, expenditure, format, render_options).await
+}
+
+async fn generate_utilisation_certificate_from_dms(
+    State(state): State<AppState>,
+    Path(unique_id): Path<String>,
+    Query(options): Query<DownloadOptions>,
+    Query(report_options): Query<ReportOptions>,
+    Query(render_options): Query<RenderOptions>,
+    Json(expenditure): Json<Expenditure>,
+) -> Response<Body> {
+    let submission = match state.dms.fetch_submission(&unique_id).await {
+        Ok(submission) => submission,
+        Err(e) => return dms_error_response(e),
+    };
+    if submission.is_discarded() && !options.force {
+        return discarded_response(&submission);
+    }
+    let format = report_options.format;
+    render_report(&state, ReportKind::Utilisation, submission, expenditure, format, render_options).await
+}
+
+#[derive(Deserialize)]
+struct SummaryOptions {
+    #[serde(default)]
+    format: summary::SummaryFormat,
+}
+
+// Whether a body of submissions is NDJSON (one per line) rather than a JSON array
+fn is_ndjson(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("ndjson") || v.contains("jsonl"))
+}
+
+// Committee summary of the submissions in the body (a JSON array or NDJSON,
+// as for batches). Discarded submissions are left out unless forced.
+async fn generate_summary(
+    State(state): State<AppState>,
+    Query(options): Query<DownloadOptions>,
+    Query(summary_options): Query<SummaryOptions>,
+    Query(render_options): Query<RenderOptions>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response<Body> {
+    let ndjson = is_ndjson(&headers);
+
+    let items = match batch::parse_submissions(&body, ndjson) {
+        Ok(items) if !items.is_empty() => items,
+        Ok(_) => {
+            return Response::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .body(Body::from("No submissions in request body"))
+                .unwrap();
+        }
+        Err(e) => {
+            println!("Error parsing summary request: {}", e);
+            return Response::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .body(Body::from(e))
+                .unwrap();
+        }
+    };
+
+    // a summary missing a proposal would mislead the committee, so any
+    // invalid entry rejects the request
+    let mut submissions = Vec::with_capacity(items.len());
+    for item in items {
+        match item.submission {
+            Ok(submission) => submissions.push(submission),
+            Err(e) => {
+                return Response::builder()
+                    .status(StatusCode::BAD_REQUEST)
+                    .body(Body::from(format!("Invalid submission at index {}: {}", item.index, e)))
                     .unwrap();
+            }
+        }
+    }
+    let total = submissions.len();
+    if !options.force {
+        submissions.retain(|submission| !submission.is_discarded());
+    }
+    let summary = summary::CommitteeSummary::new(&submissions, total - submissions.len());
+    println!("Summarizing {} submissions", summary.rows.len());
 
-                // clean up files after creating the response
-                if let Err(e) = fs::remove_file(&docx_path) {
-                    println!("Error removing DOCX file: {}", e);
+    let format = match summary_options.format {
+        summary::SummaryFormat::Csv => {
+            let csv = sheet::to_csv(&summary::summary_sheets(&summary));
+            return attachment("text/csv; charset=utf-8", &summary::summary_filename("csv"), csv.into_bytes());
+        }
+        summary::SummaryFormat::Xlsx => {
+            return match sheet::to_xlsx(&summary::summary_sheets(&summary)) {
+                Ok(xlsx) => attachment(
+                    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+                    &summary::summary_filename("xlsx"),
+                    xlsx,
+                ),
+                Err(e) => {
+                    println!("Error writing summary workbook: {}", e);
+                    Response::builder()
+                        .status(StatusCode::INTERNAL_SERVER_ERROR)
+                        .body(Body::from("Failed to generate spreadsheet"))
+                        .unwrap()
                 }
+            };
+        }
+        summary::SummaryFormat::Document(format) => format,
+    };
+    if format == DocumentFormat::Html {
+        return html_response(&state, &render_options, |styles| summary::summary_document(&summary, styles));
+    }
+    let filename = summary::summary_filename(format.extension());
+    document_file(&state, format, filename, "summary", render_options, None, move |config, options| {
+        render::build_summary_docx(&summary, config, options)
+    })
+    .await
+}
+
+#[derive(Deserialize)]
+struct BudgetOptions {
+    // "xlsx" (default) or "csv"
+    format: Option<BudgetFormat>,
+}
+
+// The submission's budget as a spreadsheet for the finance office
+fn budget_response(submission: &Submission, format: Option<BudgetFormat>) -> Response<Body> {
+    let format = format.unwrap_or(BudgetFormat::Xlsx);
+    match budget::export(submission, format) {
+        Ok(content) => attachment(
+            format.content_type(),
+            &budget::budget_filename(submission, format),
+            content,
+        ),
+        Err(e) => {
+            println!("Error exporting budget of {}: {}", submission.unique_id, e);
+            Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Body::from("Failed to generate spreadsheet"))
+                .unwrap()
+        }
+    }
+}
+
+async fn generate_budget_from_json(
+    Query(options): Query<BudgetOptions>,
+    Json(submission): Json<Submission>,
+) -> Response<Body> {
+    budget_response(&submission, options.format)
+}
 
-                response
+async fn generate_budget_from_dms(
+    State(state): State<AppState>,
+    Path(unique_id): Path<String>,
+    Query(options): Query<BudgetOptions>,
+) -> Response<Body> {
+    match state.dms.fetch_submission(&unique_id).await {
+        Ok(submission) => budget_response(&submission, options.format),
+        Err(e) => dms_error_response(e),
+    }
+}
+
+// Lists the stored versions of a submission, oldest first
+async fn list_versions(
+    State(state): State<AppState>,
+    Path(unique_id): Path<String>,
+) -> Response<Body> {
+    match state.store.versions(&unique_id) {
+        Ok(versions) if versions.is_empty() => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::from("No stored versions for this submission"))
+            .unwrap(),
+        Ok(versions) => json_response(
+            StatusCode::OK,
+            &serde_json::json!({ "unique_id": unique_id, "versions": versions }),
+        ),
+        Err(e) => store_error_response(e),
+    }
+}
+
+fn version_not_found() -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::NOT_FOUND)
+        .body(Body::from("Version not found"))
+        .unwrap()
+}
+
+// Returns the payload stored under a version
+async fn get_version(
+    State(state): State<AppState>,
+    Path((unique_id, version)): Path<(String, u32)>,
+) -> Response<Body> {
+    match state.store.version(&unique_id, version) {
+        Ok(Some(stored)) => json_response(StatusCode::OK, &stored),
+        Ok(None) => version_not_found(),
+        Err(e) => store_error_response(e),
+    }
+}
+
+// Re-renders the proposal exactly as it was stored under a version
+async fn generate_document_from_version(
+    State(state): State<AppState>,
+    Path((unique_id, version)): Path<(String, u32)>,
+    Query(options): Query<DownloadOptions>,
+    Query(render_options): Query<RenderOptions>,
+    headers: HeaderMap,
+) -> Response<Body> {
+    let submission = match state.store.version(&unique_id, version) {
+        Ok(Some(stored)) => match stored.submission() {
+            Ok(submission) => submission,
+            Err(e) => return store_error_response(e),
+        },
+        Ok(None) => return version_not_found(),
+        Err(e) => return store_error_response(e),
+    };
+
+    if submission.is_discarded() && !options.force {
+        return discarded_response(&submission);
+    }
+
+    render_in_background(&state, submission, render_options, Some(version), if_none_match(&headers)).await
+}
+
+fn dms_error_response(error: DmsError) -> Response<Body> {
+    println!("DMS error: {}", error);
+    let (status, message) = match error {
+        DmsError::NotConfigured => (StatusCode::SERVICE_UNAVAILABLE, "DMS connection is not configured"),
+        DmsError::NotFound => (StatusCode::NOT_FOUND, "Submission not found"),
+        _ => (StatusCode::BAD_GATEWAY, "Failed to fetch submission from DMS"),
+    };
+    Response::builder()
+        .status(status)
+        .body(Body::from(message))
+        .unwrap()
+}
+
+fn json_response<T: serde::Serialize>(status: StatusCode, value: &T) -> Response<Body> {
+    match serde_json::to_string(value) {
+        Ok(json) => Response::builder()
+            .status(status)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(json))
+            .unwrap(),
+        Err(_) => Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .body(Body::from("Failed to serialize response"))
+            .unwrap(),
+    }
+}
+
+fn job_view(job: &jobs::Job) -> serde_json::Value {
+    let mut view = serde_json::to_value(job).unwrap_or_default();
+    view["progress"] = serde_json::json!(job.progress());
+    view["status_url"] = serde_json::json!(format!("/jobs/{}", job.id));
+    if job.status == JobStatus::Done {
+        view["result_url"] = serde_json::json!(format!("/jobs/{}/result", job.id));
+    }
+    view
+}
+
+// Queues a render in the background. A single Submission object produces a
+// PDF; a JSON array or NDJSON produces a ZIP like /submissions/batch.
+async fn create_job(
+    State(state): State<AppState>,
+    Query(options): Query<BatchOptions>,
+    Query(render_options): Query<RenderOptions>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response<Body> {
+    let ndjson = is_ndjson(&headers);
+    let single = !ndjson && body.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'{');
+
+    let input = if single {
+        let parsed = serde_json::from_slice::<serde_json::Value>(&body)
+            .and_then(|raw| Ok((Submission::deserialize(&raw)?, raw)));
+        match parsed {
+            Ok((submission, raw)) => {
+                record_version(&state.store, &submission, &raw, "job");
+                JobInput::Single(Box::new(submission))
             }
             Err(e) => {
-                println!("Error reading PDF file: {}", e);
-                println!("DOCX file path: {}", docx_path);
-                println!("PDF file path: {}", pdf_path);
-                // List all files in the output directory
-                if let Ok(entries) = fs::read_dir("output") {
-                    println!("Files in output directory:");
-                    for entry in entries {
-                        if let Ok(entry) = entry {
-                            println!("  {}", entry.path().display());
-                        }
-                    }
-                }
-                Response::builder()
-                    .status(StatusCode::INTERNAL_SERVER_ERROR)
-                    .body(Body::from("Failed to read PDF file"))
-                    .unwrap()
+                return Response::builder()
+                    .status(StatusCode::BAD_REQUEST)
+                    .body(Body::from(format!("Invalid submission: {}", e)))
+                    .unwrap();
             }
-        },
+        }
+    } else {
+        match batch::parse_submissions(&body, ndjson) {
+            Ok(items) if !items.is_empty() => {
+                record_items(&state.store, &items, "job");
+                JobInput::Batch(items)
+            }
+            Ok(_) => {
+                return Response::builder()
+                    .status(StatusCode::BAD_REQUEST)
+                    .body(Body::from("No submissions in request body"))
+                    .unwrap();
+            }
+            Err(e) => {
+                return Response::builder()
+                    .status(StatusCode::BAD_REQUEST)
+                    .body(Body::from(e))
+                    .unwrap();
+            }
+        }
+    };
+
+    let job = state.jobs.submit(input, state.config.clone(), render_options, options);
+    json_response(StatusCode::ACCEPTED, &job_view(&job))
+}
+
+async fn get_job(State(state): State<AppState>, Path(id): Path<String>) -> Response<Body> {
+    match state.jobs.get(&id) {
+        Some(job) => json_response(StatusCode::OK, &job_view(&job)),
+        None => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::from("Job not found"))
+            .unwrap(),
+    }
+}
+
+async fn get_job_result(State(state): State<AppState>, Path(id): Path<String>) -> Response<Body> {
+    let Some(job) = state.jobs.get(&id) else {
+        return Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::from("Job not found"))
+            .unwrap();
+    };
+
+    let path = match (job.status, &job.result_path) {
+        (JobStatus::Done, Some(path)) => path.clone(),
+        (JobStatus::Failed, _) => {
+            return Response::builder()
+                .status(StatusCode::CONFLICT)
+                .body(Body::from(job.error.unwrap_or_else(|| "Job failed".to_string())))
+                .unwrap();
+        }
+        _ => {
+            return Response::builder()
+                .status(StatusCode::CONFLICT)
+                .body(Body::from("Job has not finished yet"))
+                .unwrap();
+        }
+    };
+
+    match tokio::fs::read(&path).await {
+        Ok(content) => Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, job.content_type)
+            .header(
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", job.filename),
+            )
+            .body(Body::from(content))
+            .unwrap(),
         Err(e) => {
-            println!("Error converting to PDF: {}", e);
-            println!("DOCX file path: {}", docx_path);
+            println!("Error reading job result {}: {}", path.display(), e);
             Response::builder()
-                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .body(Body::from("Failed to generate PDF"))
+                .status(StatusCode::GONE)
+                .body(Body::from("Job result is no longer available"))
                 .unwrap()
         }
+    }
+}
+
+// Checks that a PDF was signed by this service and hasn't changed since,
+// and reports the submission it was generated for
+async fn verify_pdf(State(state): State<AppState>, body: Bytes) -> Response<Body> {
+    let Some(signer) = &state.config.signing.signer else {
+        return Response::builder()
+            .status(StatusCode::SERVICE_UNAVAILABLE)
+            .body(Body::from("PDF signing is not configured"))
+            .unwrap();
     };
+    if !body.starts_with(b"%PDF-") {
+        return Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .body(Body::from("Request body is not a PDF"))
+            .unwrap();
+    }
 
-    response
+    let verification = signing::verify(&body, signer);
+    println!(
+        "Verified PDF for {}: valid={}",
+        verification.unique_id.as_deref().unwrap_or("unknown submission"),
+        verification.valid
+    );
+    json_response(StatusCode::OK, &verification)
 }
 
 async fn root() -> &'static str {
@@ -151,12 +1260,76 @@ async fn main() {
     let cors = CorsLayer::new()
         .allow_origin(Any) // Allow requests from any origin
         .allow_methods(Any) // Allow any HTTP method
-        .allow_headers(Any); // Allow any HTTP header
+        .allow_headers(Any) // Allow any HTTP header
+        .expose_headers(Any); // Let the UI read pagination headers
+
+    let config = Arc::new(Config::from_env());
+    fonts::install(&config.fonts_dir);
+    let state = AppState {
+        jobs: JobStore::new(config.job_concurrency),
+        dms: Arc::new(DmsClient::new(config.dms.clone())),
+        cache: RenderCache::new(&config.cache),
+        store: SubmissionStore::open(&config.store_path).expect("Failed to open submission store"),
+        config: config.clone(),
+    };
+
+    // periodically drop expired jobs, old files in output/ and stale cache entries
+    let jobs = state.jobs.clone();
+    let cache = state.cache.clone();
+    let ttl = chrono::Duration::seconds(config.job_ttl_secs);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            jobs.cleanup(ttl);
+            cache.evict();
+        }
+    });
 
     let app = Router::new()
         .route("/", get(root))
         .route("/submissions/download", post(generate_document_from_json)) // New POST endpoint
-        .layer(cors); // Add the CORS layer to the router
+        .route("/submissions/preview", post(generate_preview))
+        .route("/submissions/evaluation", post(generate_evaluation_from_json))
+        .route("/submissions/budget", post(generate_budget_from_json))
+        .route("/submissions/sanction-letter", post(generate_sanction_letter_from_json))
+        .route("/submissions/progress-report", post(generate_progress_report_from_json))
+        .route("/submissions/utilisation-certificate", post(generate_utilisation_certificate_from_json))
+        .route(
+            "/submissions/summary",
+            post(generate_summary).layer(DefaultBodyLimit::max(64 * 1024 * 1024)),
+        )
+        .route("/verify", post(verify_pdf).layer(DefaultBodyLimit::max(64 * 1024 * 1024)))
+        .route(
+            "/submissions/batch",
+            post(generate_batch).layer(DefaultBodyLimit::max(64 * 1024 * 1024)),
+        )
+        .route(
+            "/jobs",
+            post(create_job).layer(DefaultBodyLimit::max(64 * 1024 * 1024)),
+        )
+        .route("/submissions", get(list_submissions))
+        .route("/submissions/{unique_id}/pdf", get(generate_document_from_dms))
+        .route("/submissions/{unique_id}/thumbnail", get(generate_thumbnail))
+        .route("/submissions/{unique_id}/evaluation", get(generate_evaluation_from_dms))
+        .route("/submissions/{unique_id}/budget", get(generate_budget_from_dms))
+        .route("/submissions/{unique_id}/sanction-letter", post(generate_sanction_letter_from_dms))
+        .route("/submissions/{unique_id}/progress-report", post(generate_progress_report_from_dms))
+        .route(
+            "/submissions/{unique_id}/utilisation-certificate",
+            post(generate_utilisation_certificate_from_dms),
+        )
+        .route("/submissions/{unique_id}/versions", get(list_versions))
+        .route("/submissions/{unique_id}/diff", get(generate_diff))
+        .route("/submissions/{unique_id}/versions/{version}", get(get_version))
+        .route(
+            "/submissions/{unique_id}/versions/{version}/pdf",
+            get(generate_document_from_version),
+        )
+        .route("/jobs/{id}", get(get_job))
+        .route("/jobs/{id}/result", get(get_job_result))
+        .layer(cors) // Add the CORS layer to the router
+        .with_state(state);
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:8080").await.unwrap();
     println!("server started on port 8080...");
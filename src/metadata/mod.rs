@@ -0,0 +1,82 @@
+use crate::models::Submission;
+use chrono::{DateTime, SecondsFormat, Utc};
+use docx_rs::{Docx, XMLDocx};
+
+// Document properties taken from the submission. They go into the DOCX core
+// and custom properties; LibreOffice carries title, author, subject and
+// keywords over into the PDF document information (and XMP metadata), which
+// the DMS search index reads.
+
+pub struct DocumentInfo {
+    pub title: String,
+    pub author: String,
+    pub subject: String,
+    pub keywords: Vec<String>,
+    pub created: DateTime<Utc>,
+    pub modified: DateTime<Utc>,
+}
+
+impl DocumentInfo {
+    pub fn from_submission(submission: &Submission) -> Self {
+        Self {
+            title: submission
+                .project_title
+                .clone()
+                .unwrap_or_else(|| submission.unique_id.clone()),
+            // the PI
+            author: submission.user.clone(),
+            subject: submission.track.clone(),
+            keywords: submission.project_keywords.clone().unwrap_or_default(),
+            created: submission.created_at.unwrap_or(submission.updated_at),
+            modified: submission.updated_at,
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// docProps/core.xml. docx-rs only lets us set the dates, so the part is
+// written out in full.
+fn core_xml(info: &DocumentInfo) -> Vec<u8> {
+    let date = |value: &DateTime<Utc>| value.to_rfc3339_opts(SecondsFormat::Secs, true);
+    format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            r#"<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" "#,
+            r#"xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/" "#,
+            r#"xmlns:dcmitype="http://purl.org/dc/dcmitype/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">"#,
+            "<dc:title>{title}</dc:title>",
+            "<dc:subject>{subject}</dc:subject>",
+            "<dc:creator>{author}</dc:creator>",
+            "<cp:keywords>{keywords}</cp:keywords>",
+            "<cp:lastModifiedBy>{author}</cp:lastModifiedBy>",
+            "<cp:revision>1</cp:revision>",
+            r#"<dcterms:created xsi:type="dcterms:W3CDTF">{created}</dcterms:created>"#,
+            r#"<dcterms:modified xsi:type="dcterms:W3CDTF">{modified}</dcterms:modified>"#,
+            "</cp:coreProperties>"
+        ),
+        title = escape(&info.title),
+        subject = escape(&info.subject),
+        author = escape(&info.author),
+        keywords = escape(&info.keywords.join(", ")),
+        created = date(&info.created),
+        modified = date(&info.modified),
+    )
+    .into_bytes()
+}
+
+// Custom properties identifying the submission
+pub fn add_custom_properties(docx: Docx, submission: &Submission) -> Docx {
+    docx.custom_property("unique_id", submission.unique_id.as_str())
+        .custom_property("status", submission.status.to_string())
+}
+
+// Replaces the built document's core properties
+pub fn apply_core_properties(xml: &mut XMLDocx, info: &DocumentInfo) {
+    xml.doc_props.core = core_xml(info);
+}
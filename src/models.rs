@@ -6,6 +6,11 @@ use axum::{
 };
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+use crate::config::CoverDateSource;
+use crate::extjson::{self, ObjectId};
 
 #[derive(Serialize)]
 pub struct LoginRequest {
@@ -13,16 +18,218 @@ pub struct LoginRequest {
     pub password: String,
 }
 
-#[allow(dead_code)]
 #[derive(Deserialize)]
 pub struct LoginResponse {
     pub token: String,
 }
 
+// Enums for the free-form strings the DMS stores. Values we don't recognise
+// are kept verbatim in the `Unknown`/`Other` variant so a new status or track
+// added upstream never makes a submission fail to deserialize.
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub enum SubmissionStatus {
+    Draft,
+    Submitted,
+    UnderReview,
+    Approved,
+    Rejected,
+    Discarded,
+    Unknown(String),
+}
+
+impl From<String> for SubmissionStatus {
+    fn from(value: String) -> Self {
+        match value.trim().to_lowercase().replace(['-', ' '], "_").as_str() {
+            "draft" => Self::Draft,
+            "submitted" => Self::Submitted,
+            "under_review" | "in_review" => Self::UnderReview,
+            "approved" | "accepted" => Self::Approved,
+            "rejected" => Self::Rejected,
+            "discarded" => Self::Discarded,
+            _ => Self::Unknown(value),
+        }
+    }
+}
+
+impl From<SubmissionStatus> for String {
+    fn from(value: SubmissionStatus) -> Self {
+        match value {
+            SubmissionStatus::Draft => "draft".to_string(),
+            SubmissionStatus::Submitted => "submitted".to_string(),
+            SubmissionStatus::UnderReview => "under_review".to_string(),
+            SubmissionStatus::Approved => "approved".to_string(),
+            SubmissionStatus::Rejected => "rejected".to_string(),
+            SubmissionStatus::Discarded => "discarded".to_string(),
+            SubmissionStatus::Unknown(other) => other,
+        }
+    }
+}
+
+impl fmt::Display for SubmissionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&String::from(self.clone()))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub enum TrackCode {
+    Cybersecurity,
+    EnergyEnvironment,
+    Other(String),
+}
+
+impl From<String> for TrackCode {
+    fn from(value: String) -> Self {
+        match value.trim().to_uppercase().as_str() {
+            "CS" => Self::Cybersecurity,
+            "EE" => Self::EnergyEnvironment,
+            _ => Self::Other(value),
+        }
+    }
+}
+
+impl From<TrackCode> for String {
+    fn from(value: TrackCode) -> Self {
+        match value {
+            TrackCode::Cybersecurity => "CS".to_string(),
+            TrackCode::EnergyEnvironment => "EE".to_string(),
+            TrackCode::Other(other) => other,
+        }
+    }
+}
+
+impl fmt::Display for TrackCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&String::from(self.clone()))
+    }
+}
+
+// Technology Readiness Level. The DMS sends strings like
+// "TRL 3: Experimental proof of concept"; only the number is significant.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub enum TrlLevel {
+    Level(u8),
+    Unknown(String),
+}
+
+impl TrlLevel {
+    pub fn description(level: u8) -> &'static str {
+        match level {
+            1 => "Basic principles observed",
+            2 => "Technology concept formulated",
+            3 => "Experimental proof of concept",
+            4 => "Technology validated in lab",
+            5 => "Technology validated in relevant environment",
+            6 => "Technology demonstrated in relevant environment",
+            7 => "System prototype demonstration in operational environment",
+            8 => "System complete and qualified",
+            _ => "Actual system proven in operational environment",
+        }
+    }
+}
+
+impl From<String> for TrlLevel {
+    fn from(value: String) -> Self {
+        let trimmed = value.trim();
+        let rest = trimmed
+            .strip_prefix("TRL")
+            .or_else(|| trimmed.strip_prefix("trl"))
+            .unwrap_or(trimmed)
+            .trim_start_matches([' ', '-', '_']);
+        let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+
+        match digits.parse::<u8>() {
+            Ok(level @ 1..=9) => Self::Level(level),
+            _ => Self::Unknown(value),
+        }
+    }
+}
+
+impl From<TrlLevel> for String {
+    fn from(value: TrlLevel) -> Self {
+        match value {
+            TrlLevel::Level(level) => format!("TRL {}: {}", level, TrlLevel::description(level)),
+            TrlLevel::Unknown(other) => other,
+        }
+    }
+}
+
+impl fmt::Display for TrlLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&String::from(self.clone()))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub enum CoPIStatus {
+    Pending,
+    Confirmed,
+    Declined,
+    Unknown(String),
+}
+
+impl From<String> for CoPIStatus {
+    fn from(value: String) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "pending" | "invited" => Self::Pending,
+            "confirmed" | "accepted" => Self::Confirmed,
+            "declined" | "rejected" => Self::Declined,
+            _ => Self::Unknown(value),
+        }
+    }
+}
+
+impl From<CoPIStatus> for String {
+    fn from(value: CoPIStatus) -> Self {
+        match value {
+            CoPIStatus::Pending => "Pending".to_string(),
+            CoPIStatus::Confirmed => "Confirmed".to_string(),
+            CoPIStatus::Declined => "Declined".to_string(),
+            CoPIStatus::Unknown(other) => other,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub enum AccessLevel {
+    FullAccess,
+    ViewOnly,
+    Unknown(String),
+}
+
+impl From<String> for AccessLevel {
+    fn from(value: String) -> Self {
+        match value.trim().to_lowercase().replace(['-', '_'], " ").as_str() {
+            "full access" | "full" | "edit" => Self::FullAccess,
+            "view only" | "view" | "read only" => Self::ViewOnly,
+            _ => Self::Unknown(value),
+        }
+    }
+}
+
+impl From<AccessLevel> for String {
+    fn from(value: AccessLevel) -> Self {
+        match value {
+            AccessLevel::FullAccess => "Full Access".to_string(),
+            AccessLevel::ViewOnly => "View Only".to_string(),
+            AccessLevel::Unknown(other) => other,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ProjectDuration {
+    #[serde(deserialize_with = "extjson::int")]
     pub days: i32,
+    #[serde(deserialize_with = "extjson::int")]
     pub months: i32,
+    #[serde(deserialize_with = "extjson::int")]
     pub years: i32,
 }
 
@@ -30,9 +237,10 @@ pub struct ProjectDuration {
 pub struct CoPI {
     pub email: String,
     pub name: String,
-    pub status: String,
-    #[serde(default)]
-    pub accessLevel: Option<String>,
+    pub status: CoPIStatus,
+    #[serde(rename = "accessLevel", default)]
+    pub access_level: Option<AccessLevel>,
+    // Free-text description of the Co-PI's part in the project
     #[serde(default)]
     pub role: Option<String>,
 }
@@ -41,9 +249,12 @@ pub struct CoPI {
 pub struct BudgetItem {
     pub heading: String,
     pub id: String,
-    pub isRequired: bool,
+    #[serde(rename = "isRequired")]
+    pub is_required: bool,
     pub justification: String,
+    #[serde(deserialize_with = "extjson::int")]
     pub total: i32,
+    #[serde(deserialize_with = "extjson::int_vec")]
     pub years: Vec<i32>,
 }
 
@@ -55,22 +266,24 @@ pub struct BudgetCategory {
 }
 
 #[allow(dead_code)]
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Submission {
-    #[serde(rename = "_id")]
-    pub id: Option<serde_json::Value>,
-    pub status: String,
+    #[serde(rename = "_id", default)]
+    pub id: Option<ObjectId>,
+    pub status: SubmissionStatus,
     pub track: String,
     #[serde(rename = "trackCode", default)]
-    pub track_code: Option<String>,
+    pub track_code: Option<TrackCode>,
     #[serde(rename = "unique_id")]
     pub unique_id: String,
-    #[serde(rename = "updatedAt")]
-    pub updated_at: serde_json::Value, // Could be DateTime<Utc> or a custom format
+    #[serde(rename = "updatedAt", deserialize_with = "extjson::date")]
+    pub updated_at: DateTime<Utc>,
     pub user: String,
-    #[serde(rename = "createdAt", default)]
-    pub created_at: Option<String>,
-    #[serde(rename = "maxFilled", default)]
+    #[serde(rename = "createdAt", default, deserialize_with = "extjson::option_date")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(rename = "submittedAt", default, deserialize_with = "extjson::option_date")]
+    pub submitted_at: Option<DateTime<Utc>>,
+    #[serde(rename = "maxFilled", default, deserialize_with = "extjson::option_int")]
     pub max_filled: Option<i32>,
     #[serde(rename = "additional_information", default)]
     pub additional_information: Option<String>,
@@ -127,26 +340,53 @@ pub struct Submission {
     #[serde(rename = "timeline_diagram", default)]
     pub timeline_diagram: Option<Vec<String>>,
     #[serde(rename = "trl_level", default)]
-    pub trl_level: Option<String>,
+    pub trl_level: Option<TrlLevel>,
     #[serde(rename = "work_organization", default)]
     pub work_organization: Option<String>,
     #[serde(rename = "budget", default)]
     pub budget: Option<Vec<BudgetCategory>>,
     #[serde(rename = "coPI", default)]
     pub co_pi: Option<Vec<CoPI>>,
-    #[serde(rename = "discardedAt", default)]
-    pub discarded_at: Option<serde_json::Value>,
+    #[serde(rename = "discardedAt", default, deserialize_with = "extjson::option_date")]
+    pub discarded_at: Option<DateTime<Utc>>,
 }
+
+impl Submission {
+    // Discarded proposals are withdrawn by the applicant and should not be
+    // rendered unless explicitly requested.
+    pub fn is_discarded(&self) -> bool {
+        self.status == SubmissionStatus::Discarded || self.discarded_at.is_some()
+    }
+
+    // Resolves the date printed on the cover page, falling back to the last
+    // update (always present) and finally to the time of generation.
+    pub fn cover_date(&self, source: CoverDateSource) -> DateTime<Utc> {
+        let date = match source {
+            CoverDateSource::Generated => None,
+            CoverDateSource::Created => self.created_at.or(Some(self.updated_at)),
+            CoverDateSource::Updated => Some(self.updated_at),
+            CoverDateSource::Submitted => self.submitted_at.or(Some(self.updated_at)),
+        };
+        date.unwrap_or_else(Utc::now)
+    }
+
+    // SHA-256 of the normalized submission, identifying the exact content a
+    // document was generated from
+    pub fn content_hash(&self) -> String {
+        format!("{:x}", Sha256::digest(serde_json::to_vec(self).unwrap_or_default()))
+    }
+}
+
 #[derive(Serialize)]
 pub struct SubmissionResponse {
     id: String,
-    status: String,
+    status: SubmissionStatus,
     track: String,
-    track_code: Option<String>,
+    track_code: Option<TrackCode>,
     unique_id: String,
-    updated_at: String,
+    updated_at: DateTime<Utc>,
     user: String,
-    created_at: Option<String>,
+    created_at: Option<DateTime<Utc>>,
     max_filled: Option<i32>,
     additional_information: Option<String>,
     project_duration: Option<ProjectDuration>,
@@ -157,21 +397,20 @@ pub struct SubmissionResponse {
     project_title: Option<String>,
     supporting_documents: Option<Vec<String>>,
     co_pi: Option<Vec<CoPI>>,
-    discarded_at: Option<String>,
+    discarded_at: Option<DateTime<Utc>>,
 }
 
-#[allow(dead_code)]
 impl SubmissionResponse {
     pub fn from_submissions(submissions: Vec<Submission>) -> Vec<Self> {
         submissions
             .into_iter()
             .map(|s| Self {
-                id: s.id.map_or_else(|| "".to_string(), |v| v.to_string()),
+                id: s.id.map_or_else(String::new, |v| v.to_string()),
                 status: s.status,
                 track: s.track,
                 track_code: s.track_code,
                 unique_id: s.unique_id,
-                updated_at: s.updated_at.to_string(),
+                updated_at: s.updated_at,
                 user: s.user,
                 created_at: s.created_at,
                 max_filled: s.max_filled,
@@ -184,7 +423,7 @@ impl SubmissionResponse {
                 project_title: s.project_title,
                 supporting_documents: s.supporting_documents,
                 co_pi: s.co_pi,
-                discarded_at: s.discarded_at.map(|d| d.to_string()),
+                discarded_at: s.discarded_at,
             })
             .collect()
     }
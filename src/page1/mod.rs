@@ -1,32 +1,53 @@
+use crate::config::TemplateConfig;
+use crate::document::{Block, Image, Paragraph, StyleName, TextStyle};
 use crate::models::Submission;
-use docx_rs::{AlignmentType, Paragraph, Pic, Run};
+use crate::qr::{self, CoverCode};
 use std::fs::File;
 use std::io::Read;
-use chrono::Local;
+use chrono::{Local, Utc};
 
-#[allow(dead_code)]
-pub fn page1_content(submission: &Submission) -> Vec<Paragraph> {
+// Height of the QR code in twips, the same as the logo's so the cover
+// layout doesn't move
+const QR_HEIGHT: usize = 1275;
+
+fn format_cover_date(submission: &Submission, template: &TemplateConfig) -> String {
+    submission
+        .cover_date(template.cover_date)
+        .with_timezone(&Local)
+        .format(&template.cover_date_format)
+        .to_string()
+}
+
+pub fn load_logo() -> Option<Vec<u8>> {
     let mut file = match File::open("./public/thapar_logo.png") {
         Ok(file) => file,
         Err(e) => {
             println!("Failed to open logo file: {}", e);
-            // Return a placeholder if logo can't be loaded
-            return create_placeholder_content(submission);
+            return None;
         }
     };
     let mut buffer = Vec::new();
     if let Err(e) = file.read_to_end(&mut buffer) {
         println!("Failed to read logo file: {}", e);
-        return create_placeholder_content(submission);
+        return None;
     }
-    
-    let image = Pic::new(&buffer);
-    let current_date = Local::now();
-    let formatted_date = format!("{} {}", current_date.format("%B"), current_date.format("%Y"));
-    
+    Some(buffer)
+}
+
+fn cover_qr(submission: &Submission, template: &TemplateConfig) -> Option<Vec<u8>> {
+    let code = CoverCode::new(submission, Utc::now());
+    qr::png(&code.payload(template.verify_url_prefix.as_deref()))
+}
+
+// Cover page. Without the logo file the page is laid out the same, just
+// without the image. With `qr` the verification QR code sits next to the
+// logo.
+pub fn page1_content(submission: &Submission, template: &TemplateConfig, qr: bool) -> Vec<Block> {
+    let formatted_date = format_cover_date(submission, template);
+
     // Maximum number of Co-PIs we'll display individually
     const MAX_COPI_DISPLAY: usize = 5;
-    
+
     // Extract Co-PI emails
     let mut co_pi_emails = Vec::new();
     if let Some(co_pis) = &submission.co_pi {
@@ -34,83 +55,47 @@ pub fn page1_content(submission: &Submission) -> Vec<Paragraph> {
             co_pi_emails.push(co_pi.email.clone());
         }
     }
-    
+
     // Check if there are any co-PIs
     let has_co_pis = !co_pi_emails.is_empty();
-    
-    let mut paragraphs = vec![
+
+    let mut blocks = vec![
         // Project Title at the top
-        Paragraph::new().align(AlignmentType::Center).add_run(
-            Run::new()
-                .add_text(
-                    submission
-                        .project_title
-                        .as_ref()
-                        .unwrap_or(&"<Title>".to_string()),
-                )
-                .bold()
-                .size(48)
-        ),
-        Paragraph::new(),
-        Paragraph::new(),
-        // COE-DSAI title 
-        Paragraph::new().align(AlignmentType::Center).add_run(
-            Run::new()
-                .add_text("COE-DSAI SEED GRANT PROPOSAL")
-                .bold()
-                .size(48)
-                .color("#800020") // Burgundy color to match the image
-        ),
-        Paragraph::new(),
-        Paragraph::new(),
-        Paragraph::new(),
+        Block::styled(submission.project_title.as_deref().unwrap_or("<Title>"), StyleName::Title),
+        Block::Blank,
+        Block::Blank,
+        // COE-DSAI title
+        Block::styled("COE-DSAI SEED GRANT PROPOSAL", StyleName::Subtitle),
+        Block::Blank,
+        Block::Blank,
+        Block::Blank,
         // PI Details subtitle
-        Paragraph::new().align(AlignmentType::Center).add_run(
-            Run::new()
-                .add_text("PI Details")
-                .bold()
-                .size(48)
-        ),
-        Paragraph::new(),
+        Block::styled("PI Details", StyleName::CoverHeading),
+        Block::Blank,
         // Principal Investigator label
-        Paragraph::new().align(AlignmentType::Center).add_run(
-            Run::new()
-                .add_text("Principal Investigator")
-                .size(36)
-        ),
-        Paragraph::new(),
+        Block::styled("Principal Investigator", StyleName::CoverText),
+        Block::Blank,
         // User email (PI email)
-        Paragraph::new().align(AlignmentType::Center).add_run(
-            Run::new()
-                .add_text(&submission.user)
-                .bold()
-                .size(40)
-        ),
-        Paragraph::new(),
+        Block::styled(&submission.user, StyleName::CoverName),
+        Block::Blank,
     ];
-    
+
     // Add Co-PI label and emails if there are co-PIs
     if has_co_pis {
-        // Add Co-PI label
-        paragraphs.push(
-            Paragraph::new().align(AlignmentType::Center).add_run(
-                Run::new()
-                    .add_text("Co-Principal Investigator(s)")
-                    .size(32)
-            )
-        );
-        paragraphs.push(Paragraph::new());
-        
+        // Add Co-PI label, a little smaller than the PI's
+        blocks.push(Block::Paragraph(
+            Paragraph::styled(StyleName::CoverText).text("Co-Principal Investigator(s)", TextStyle::new().size(32)),
+        ));
+        blocks.push(Block::Blank);
+
         // Add Co-PI emails
         for email in &co_pi_emails {
-            paragraphs.push(
-                Paragraph::new()
-                    .align(AlignmentType::Center)
-                    .add_run(Run::new().add_text(email).bold().size(36))
-            );
+            blocks.push(Block::Paragraph(
+                Paragraph::styled(StyleName::CoverName).text(email, TextStyle::new().size(36)),
+            ));
         }
     }
-    
+
     // Add fixed number of blank paragraphs to maintain layout
     // If we have Co-PIs, we'll add fewer blank paragraphs to compensate
     let blank_paragraphs_needed = if has_co_pis {
@@ -119,181 +104,42 @@ pub fn page1_content(submission: &Submission) -> Vec<Paragraph> {
     } else {
         8 // Maximum number of blank paragraphs
     };
-    
+
     for _ in 0..blank_paragraphs_needed {
-        paragraphs.push(Paragraph::new());
+        blocks.push(Block::Blank);
     }
-    
+
     // Add the rest of the content at the bottom of the page
-    paragraphs.extend(vec![
+    let qr_code = if qr { cover_qr(submission, template) } else { None };
+    match (load_logo(), qr_code) {
         // Thapar Logo
-        Paragraph::new()
-            .align(AlignmentType::Center)
-            .add_run(Run::new().add_image(image)),
-        Paragraph::new(),
-        // Centre of Excellence text
-        Paragraph::new()
-            .align(AlignmentType::Center)
-            .add_run(Run::new().add_text("Centre of Excellence in Data Science and").bold().size(36).color("#0066CC")), // Blue color to match the image
-        Paragraph::new()
-            .align(AlignmentType::Center)
-            .add_run(Run::new().add_text("Artificial Intelligence").bold().size(36).color("#0066CC")),
-        Paragraph::new(),
-        // Institution name
-        Paragraph::new().align(AlignmentType::Center).add_run(
-            Run::new()
-                .add_text("Thapar Institute of Engineering and Technology")
-                .bold()
-                .size(36)
-        ),
-        Paragraph::new(),
-        // Address
-        Paragraph::new()
-            .align(AlignmentType::Center)
-            .add_run(Run::new().add_text("Patiala 147004").bold().size(36)),
-        Paragraph::new(),
-        // Date
-        Paragraph::new()
-            .align(AlignmentType::Center)
-            .add_run(Run::new().add_text(&formatted_date).size(36)),
-    ]);
-    
-    paragraphs
-}
-
-fn create_placeholder_content(submission: &Submission) -> Vec<Paragraph> {
-    // A simplified version without the logo
-    let current_date = Local::now();
-    let formatted_date = format!("{} {}", current_date.format("%B"), current_date.format("%Y"));
-    
-    // Maximum number of Co-PIs we'll display individually
-    const MAX_COPI_DISPLAY: usize = 5;
-    
-    // Extract Co-PI emails
-    let mut co_pi_emails = Vec::new();
-    if let Some(co_pis) = &submission.co_pi {
-        for co_pi in co_pis.iter().take(MAX_COPI_DISPLAY) {
-            co_pi_emails.push(co_pi.email.clone());
+        (Some(logo), None) => {
+            blocks.push(Block::Image(logo));
+            blocks.push(Block::Blank);
         }
-    }
-    
-    // Check if there are any co-PIs
-    let has_co_pis = !co_pi_emails.is_empty();
-    
-    let mut paragraphs = vec![
-        // Project Title at the top
-        Paragraph::new().align(AlignmentType::Center).add_run(
-            Run::new()
-                .add_text(
-                    submission
-                        .project_title
-                        .as_ref()
-                        .unwrap_or(&"<Title>".to_string()),
-                )
-                .bold()
-                .size(48)
-        ),
-        Paragraph::new(),
-        Paragraph::new(),
-        // COE-DSAI title
-        Paragraph::new().align(AlignmentType::Center).add_run(
-            Run::new()
-                .add_text("COE-DSAI SEED GRANT PROPOSAL")
-                .bold()
-                .size(48)
-                .color("#800020") // Burgundy color to match the image
-        ),
-        Paragraph::new(),
-        Paragraph::new(),
-        Paragraph::new(),
-        // PI Details subtitle
-        Paragraph::new().align(AlignmentType::Center).add_run(
-            Run::new()
-                .add_text("PI Details")
-                .bold()
-                .size(48)
-        ),
-        Paragraph::new(),
-        // Principal Investigator label
-        Paragraph::new().align(AlignmentType::Center).add_run(
-            Run::new()
-                .add_text("Principal Investigator")
-                .size(36)
-        ),
-        Paragraph::new(),
-        // User email (PI email)
-        Paragraph::new().align(AlignmentType::Center).add_run(
-            Run::new()
-                .add_text(&submission.user)
-                .bold()
-                .size(40)
-        ),
-        Paragraph::new(),
-    ];
-    
-    // Add Co-PI label and emails if there are co-PIs
-    if has_co_pis {
-        // Add Co-PI label
-        paragraphs.push(
-            Paragraph::new().align(AlignmentType::Center).add_run(
-                Run::new()
-                    .add_text("Co-Principal Investigator(s)")
-                    .size(32)
-            )
-        );
-        paragraphs.push(Paragraph::new());
-        
-        // Add Co-PI emails
-        for email in &co_pi_emails {
-            paragraphs.push(
-                Paragraph::new()
-                    .align(AlignmentType::Center)
-                    .add_run(Run::new().add_text(email).bold().size(36))
-            );
+        (logo, Some(qr_code)) => {
+            let mut images: Vec<Image> = logo.into_iter().map(Image::new).collect();
+            images.push(Image::new(qr_code).height(QR_HEIGHT));
+            blocks.push(Block::ImageRow(images));
+            blocks.push(Block::Blank);
         }
+        (None, None) => {}
     }
-    
-    // Add fixed number of blank paragraphs to maintain layout
-    // If we have Co-PIs, we'll add fewer blank paragraphs to compensate
-    let blank_paragraphs_needed = if has_co_pis {
-        // 2 for the Co-PI label + number of Co-PI emails
-        8 - (2 + co_pi_emails.len()).min(7)
-    } else {
-        8 // Maximum number of blank paragraphs
-    };
-    
-    for _ in 0..blank_paragraphs_needed {
-        paragraphs.push(Paragraph::new());
-    }
-    
-    // Add the rest of the content at the bottom of the page
-    paragraphs.extend(vec![
+
+    blocks.extend(vec![
         // Centre of Excellence text
-        Paragraph::new()
-            .align(AlignmentType::Center)
-            .add_run(Run::new().add_text("Centre of Excellence in Data Science and").bold().size(36).color("#0066CC")), // Blue color to match the image
-        Paragraph::new()
-            .align(AlignmentType::Center)
-            .add_run(Run::new().add_text("Artificial Intelligence").bold().size(36).color("#0066CC")),
-        Paragraph::new(),
+        Block::styled("Centre of Excellence in Data Science and", StyleName::Institution),
+        Block::styled("Artificial Intelligence", StyleName::Institution),
+        Block::Blank,
         // Institution name
-        Paragraph::new().align(AlignmentType::Center).add_run(
-            Run::new()
-                .add_text("Thapar Institute of Engineering and Technology")
-                .bold()
-                .size(36)
-        ),
-        Paragraph::new(),
+        Block::styled("Thapar Institute of Engineering and Technology", StyleName::CoverAddress),
+        Block::Blank,
         // Address
-        Paragraph::new()
-            .align(AlignmentType::Center)
-            .add_run(Run::new().add_text("Patiala 147004").bold().size(36)),
-        Paragraph::new(),
+        Block::styled("Patiala 147004", StyleName::CoverAddress),
+        Block::Blank,
         // Date
-        Paragraph::new()
-            .align(AlignmentType::Center)
-            .add_run(Run::new().add_text(&formatted_date).size(36)),
+        Block::styled(&formatted_date, StyleName::CoverText),
     ]);
-    
-    paragraphs
+
+    blocks
 }
@@ -1,545 +1,279 @@
-use crate::{
-    models::Submission,
-    utils::{create_paragraph, empty_row},
-};
-use docx_rs::{
-    AlignmentType, Paragraph, Run, Table, TableCell, TableCellBorderPosition, TableRow, WidthType,
-    LineSpacing, RunFonts,
-};
-
-// Helper function to create a paragraph with bold heading and normal text content
-fn create_paragraph_with_bold_heading(heading: &str, content: &str) -> Paragraph {
-    let mut paragraph = Paragraph::new();
-    // Use Calibri font for headings with black color instead of dark blue
-    paragraph = paragraph.add_run(
-        Run::new()
-            .add_text(heading)
-            .bold()
-            .size(28)
-            .fonts(RunFonts::new().ascii("Calibri"))
-            .color("#000000")  // Changed from #00355F (dark blue) to #000000 (black)
-    );
-    // Use a serif font (Georgia) for content with a dark gray color
-    paragraph = paragraph.add_run(
-        Run::new()
-            .add_text(content)
-            .size(28)
-            .fonts(RunFonts::new().ascii("Georgia"))
-            .color("#333333")  // Dark gray for better readability
-    );
-    paragraph
+use crate::document::{Block, Cell, Paragraph, Section, StyleName, Table, TextStyle};
+use crate::models::Submission;
+
+// Numbered template item: its heading, then the applicant's text if any
+fn item(blocks: &mut Vec<Block>, heading: &str, value: &str) {
+    blocks.push(Block::Heading(heading.to_string()));
+    if !value.is_empty() {
+        blocks.push(Block::Text(value.to_string()));
+    }
 }
 
-// Create an empty paragraph for spacing
-fn create_spacing_paragraph() -> Paragraph {
-    Paragraph::new().line_spacing(LineSpacing::new().after(240)) // 240 is approximately 12pt spacing
+// Bullet lines asked of every investigator in Section A
+const INVESTIGATOR_DETAILS: [&str; 5] = [
+    "Designation:",
+    "Department /School",
+    "Area of Specialization",
+    "Date of Joining the Institute",
+    "Date of Award of Ph.D Degree",
+];
+
+fn investigator_details() -> Vec<String> {
+    INVESTIGATOR_DETAILS.iter().map(|detail| detail.to_string()).collect()
 }
 
-#[allow(dead_code)]
-pub fn page2_content_with_table(submission: &Submission) -> (Vec<Paragraph>, Table) {
-    // Create the base budget table
-    let mut table_rows = vec![
-        TableRow::new(vec![
-            TableCell::new()
-                .width(500, WidthType::Dxa)
-                .add_paragraph(Paragraph::new().add_run(Run::new().add_text(""))),
-            TableCell::new()
-                .width(2000, WidthType::Dxa)
-                .add_paragraph(create_paragraph("Item")),
-            TableCell::new()
-                .width(1000, WidthType::Dxa)
-                .clear_border(TableCellBorderPosition::Right)
-                .add_paragraph(create_paragraph("Year 1")),
-            TableCell::new()
-                .width(1000, WidthType::Dxa)
-                .add_paragraph(create_paragraph("Year 2")),
-            TableCell::new()
-                .width(1000, WidthType::Dxa)
-                .clear_border(TableCellBorderPosition::Left)
-                .add_paragraph(create_paragraph("Year 3")),
-            TableCell::new()
-                .width(1500, WidthType::Dxa)
-                .add_paragraph(create_paragraph("Total")),
-            TableCell::new()
-                .width(2000, WidthType::Dxa)
-                .add_paragraph(create_paragraph("Justification")),
-        ]),
-    ];
+fn budget_table(submission: &Submission) -> Table {
+    // Header row; the year columns share inner borders
+    let mut rows = vec![vec![
+        Cell::empty().width(500),
+        Cell::new("Item").width(2000),
+        Cell { open_right: true, ..Cell::new("Year 1").width(1000) },
+        Cell::new("Year 2").width(1000),
+        Cell { open_left: true, ..Cell::new("Year 3").width(1000) },
+        Cell::new("Total").width(1500),
+        Cell::new("Justification").width(2000),
+    ]];
+    let empty_row = || vec![Cell::empty(); 7];
 
     // Add budget items if available
     if let Some(budget_categories) = &submission.budget {
         for (index, category) in budget_categories.iter().enumerate() {
             // Add category header
-            table_rows.push(
-        TableRow::new(vec![
-                    TableCell::new().add_paragraph(create_paragraph(&format!("{}", index + 1))),
-                    TableCell::new().add_paragraph(create_paragraph(&category.category_type)),
-                    TableCell::new().add_paragraph(Paragraph::new()),
-                    TableCell::new().add_paragraph(Paragraph::new()),
-                    TableCell::new().add_paragraph(Paragraph::new()),
-                    TableCell::new().add_paragraph(Paragraph::new()),
-                    TableCell::new().add_paragraph(Paragraph::new()),
-                ]),
-            );
-
-            // Add items for this category
+            let mut header = vec![Cell::new((index + 1).to_string()), Cell::new(&category.category_type)];
+            header.extend(vec![Cell::empty(); 5]);
+            rows.push(header);
+
+            // Add items for this category, always with 3 year columns
             for item in &category.items {
-                let mut year_cells = Vec::new();
-                for (i, &year_amount) in item.years.iter().enumerate().take(3) {
-                    if i < item.years.len() {
-                        year_cells.push(
-                            TableCell::new().add_paragraph(create_paragraph(&year_amount.to_string())),
-                        );
-                    } else {
-                        year_cells.push(TableCell::new().add_paragraph(create_paragraph("0")));
-                    }
-                }
-
-                // Ensure we have 3 year cells
-                while year_cells.len() < 3 {
-                    year_cells.push(TableCell::new().add_paragraph(create_paragraph("0")));
-                }
-
-                table_rows.push(TableRow::new(vec![
-                    TableCell::new().add_paragraph(Paragraph::new()),
-                    TableCell::new().add_paragraph(create_paragraph(&item.heading)),
-                    year_cells[0].clone(),
-                    year_cells[1].clone(),
-                    year_cells[2].clone(),
-                    TableCell::new().add_paragraph(create_paragraph(&item.total.to_string())),
-                    TableCell::new().add_paragraph(create_paragraph(&item.justification)),
-                ]));
+                let year = |i: usize| Cell::new(item.years.get(i).copied().unwrap_or(0).to_string());
+                rows.push(vec![
+                    Cell::empty(),
+                    Cell::new(&item.heading),
+                    year(0),
+                    year(1),
+                    year(2),
+                    Cell::new(item.total.to_string()),
+                    Cell::new(&item.justification),
+                ]);
             }
 
             // Add an empty row after each category for better readability
-            table_rows.push(TableRow::new(empty_row(7)));
+            rows.push(empty_row());
         }
     } else {
         // If no budget is provided, add default rows
-        table_rows.push(TableRow::new(vec![
-            TableCell::new().add_paragraph(create_paragraph("A")),
-            TableCell::new().add_paragraph(create_paragraph("Recurring")),
-            TableCell::new().add_paragraph(Paragraph::new()),
-            TableCell::new().add_paragraph(Paragraph::new()),
-            TableCell::new().add_paragraph(Paragraph::new()),
-            TableCell::new().add_paragraph(Paragraph::new()),
-            TableCell::new().add_paragraph(Paragraph::new()),
-        ]));
-        table_rows.push(TableRow::new(empty_row(7)));
-        table_rows.push(TableRow::new(empty_row(7)));
-        table_rows.push(TableRow::new(vec![
-            TableCell::new().add_paragraph(create_paragraph("B")),
-            TableCell::new().add_paragraph(create_paragraph("Non-Recurring")),
-            TableCell::new().add_paragraph(Paragraph::new()),
-            TableCell::new().add_paragraph(Paragraph::new()),
-            TableCell::new().add_paragraph(Paragraph::new()),
-            TableCell::new().add_paragraph(Paragraph::new()),
-            TableCell::new().add_paragraph(Paragraph::new()),
-        ]));
-        table_rows.push(TableRow::new(empty_row(7)));
+        for (letter, kind) in [("A", "Recurring"), ("B", "Non-Recurring")] {
+            let mut header = vec![Cell::new(letter), Cell::new(kind)];
+            header.extend(vec![Cell::empty(); 5]);
+            rows.push(header);
+            rows.push(empty_row());
+            if letter == "A" {
+                rows.push(empty_row());
+            }
+        }
     }
 
-    let table = Table::new(table_rows).width(100, WidthType::Pct);
+    Table { rows }
+}
 
-    let total_months = submission.project_duration.as_ref().map_or(0, |duration| {
-        (duration.years * 12) + duration.months + (duration.days as f32 / 30.0).floor() as i32
-    });
-
-    // Extract values as owned strings to avoid temporary value issues
-    let empty_string = String::new();
-    let project_title = submission.project_title.as_ref().map_or_else(|| empty_string.clone(), |s| s.clone());
-    let track = submission.track.clone();
-    let total_cost = calculate_total_budget(submission);
-    let track_code = submission.track_code.as_ref().map_or_else(|| empty_string.clone(), |s| s.clone());
-    let trl_level = submission.trl_level.as_ref().map_or_else(|| empty_string.clone(), |s| s.clone());
-    let project_summary = submission.project_summary.as_ref().map_or_else(|| empty_string.clone(), |s| s.clone());
-    let project_keywords = submission.project_keywords.as_ref()
+// Sections A and B, ending with the budget table. Section A starts on a
+// new page after the cover.
+pub fn page2_content(submission: &Submission) -> Vec<Section> {
+    let total_months = duration_months(submission);
+
+    let text = |value: &Option<String>| value.clone().unwrap_or_default();
+    let project_title = text(&submission.project_title);
+    let track_code = submission.track_code.as_ref().map_or_else(String::new, |s| s.to_string());
+    let trl_level = submission.trl_level.as_ref().map_or_else(String::new, |s| s.to_string());
+    let project_keywords = submission
+        .project_keywords
+        .as_ref()
         .map(|keyword| keyword.join(", "))
-        .unwrap_or_else(|| empty_string.clone());
-    let project_origin = submission.project_origin.as_ref().map_or_else(|| empty_string.clone(), |s| s.clone());
-    let problem_definition = submission.problem_definition.as_ref().map_or_else(|| empty_string.clone(), |s| s.clone());
-    let objectives = format_objectives(submission);
-    let international_status = submission.international_research_status.as_ref().map_or_else(|| empty_string.clone(), |s| s.clone());
-    let national_status = submission.national_research_status.as_ref().map_or_else(|| empty_string.clone(), |s| s.clone());
-    let project_importance = submission.project_importance.as_ref().map_or_else(|| empty_string.clone(), |s| s.clone());
-    let references = format_references(submission);
-    let methodology = submission.methodology.as_ref().map_or_else(|| empty_string.clone(), |s| s.clone());
-    let work_organization = submission.work_organization.as_ref().map_or_else(|| empty_string.clone(), |s| s.clone());
-    let timeline = format_timeline(submission);
-    let deliverables = format_deliverables(submission);
-    let tiet_uq_facilities = submission.tiet_uq_facilities.as_ref().map_or_else(|| empty_string.clone(), |s| s.clone());
-    let industry_partner = submission.industry_partner.as_ref().map_or_else(|| empty_string.clone(), |s| s.clone());
-    let experts = format_experts(submission);
-    let society_impact = submission.society_impact.as_ref().map_or_else(|| empty_string.clone(), |s| s.clone());
-    let user_email = submission.user.clone();
-
-    let mut paragraphs = Vec::new();
-    
-    // Section A header
-    paragraphs.push(
-        Paragraph::new()
-            .align(AlignmentType::Center)
-            .add_run(
-                Run::new()
-                    .add_text("Section A")
-                    .bold()
-                    .size(48)
-                    .fonts(RunFonts::new().ascii("Arial"))
-                    .color("#000000")  // Changed from #00355F (dark blue) to #000000 (black)
-            )
-    );
-    paragraphs.push(create_spacing_paragraph());
-    
+        .unwrap_or_default();
+
+    let mut blocks = vec![Block::Spacer];
+
     // Section A content - add spacing between each point
-    paragraphs.push(create_paragraph_with_bold_heading("1. Project Title: ", &project_title));
-    paragraphs.push(create_spacing_paragraph());
-    
-    paragraphs.push(create_paragraph_with_bold_heading("2. Sub Area: ", &track));
-    paragraphs.push(create_spacing_paragraph());
-    
-    paragraphs.push(create_paragraph_with_bold_heading("3. Total Cost: ", &total_cost));
-    paragraphs.push(create_spacing_paragraph());
-    
-    paragraphs.push(create_paragraph_with_bold_heading("4. Duration in months: ", &total_months.to_string()));
-    paragraphs.push(create_spacing_paragraph());
-    
-    paragraphs.push(create_paragraph_with_bold_heading("5. Name of the Project Investigator (UoQ/TIET):", ""));
-    
-    // Change the bullet points to be indented and non-bold for section 5
-    paragraphs.push(
-        Paragraph::new()
-            .indent(Some(500), None, None, None) // Add indentation (720 twips = 0.5 inch)
-            .add_run(
-                Run::new()
-                    .add_text("• Designation:")
-                    .size(28)
-                    .fonts(RunFonts::new().ascii("Calibri"))
-                    .color("#000000")
-            )
-    );
-    
-    paragraphs.push(
-        Paragraph::new()
-            .indent(Some(500), None, None, None)
-            .add_run(
-                Run::new()
-                    .add_text("• Department /School")
-                    .size(28)
-                    .fonts(RunFonts::new().ascii("Calibri"))
-                    .color("#000000")
-            )
-    );
-    
-    paragraphs.push(
-        Paragraph::new()
-            .indent(Some(500), None, None, None)
-            .add_run(
-                Run::new()
-                    .add_text("• Area of Specialization")
-                    .size(28)
-                    .fonts(RunFonts::new().ascii("Calibri"))
-                    .color("#000000")
-            )
-    );
-    
-    paragraphs.push(
-        Paragraph::new()
-            .indent(Some(500), None, None, None)
-            .add_run(
-                Run::new()
-                    .add_text("• Date of Joining the Institute")
-                    .size(28)
-                    .fonts(RunFonts::new().ascii("Calibri"))
-                    .color("#000000")
-            )
-    );
-    
-    paragraphs.push(
-        Paragraph::new()
-            .indent(Some(500), None, None, None)
-            .add_run(
-                Run::new()
-                    .add_text("• Date of Award of Ph.D Degree")
-                    .size(28)
-                    .fonts(RunFonts::new().ascii("Calibri"))
-                    .color("#000000")
-            )
-    );
-    
+    item(&mut blocks, "1. Project Title", &project_title);
+    blocks.push(Block::Spacer);
+
+    item(&mut blocks, "2. Sub Area", &submission.track);
+    blocks.push(Block::Spacer);
+
+    item(&mut blocks, "3. Total Cost", &calculate_total_budget(submission));
+    blocks.push(Block::Spacer);
+
+    item(&mut blocks, "4. Duration in months", &total_months.to_string());
+    blocks.push(Block::Spacer);
+
+    item(&mut blocks, "5. Name of the Project Investigator (UoQ/TIET)", "");
+    let mut pi_details = investigator_details();
+
     // Add E-Code, Contact, Email and TRL Level
     if !track_code.is_empty() {
-        paragraphs.push(
-            Paragraph::new()
-                .indent(Some(500), None, None, None)
-                .add_run(
-                    Run::new()
-                        .add_text(&format!("• E-Code: {}", track_code))
-                        .size(28)
-                        .fonts(RunFonts::new().ascii("Calibri"))
-                        .color("#000000")
-                )
-        );
+        pi_details.push(format!("E-Code: {}", track_code));
     }
-    
-    paragraphs.push(
-        Paragraph::new()
-            .indent(Some(500), None, None, None)
-            .add_run(
-                Run::new()
-                    .add_text("• Contact:")
-                    .size(28)
-                    .fonts(RunFonts::new().ascii("Calibri"))
-                    .color("#000000")
-            )
-    );
-    
-    if !user_email.is_empty() {
-        paragraphs.push(
-            Paragraph::new()
-                .indent(Some(500), None, None, None)
-                .add_run(
-                    Run::new()
-                        .add_text(&format!("• Email: {}", user_email))
-                        .size(28)
-                        .fonts(RunFonts::new().ascii("Calibri"))
-                        .color("#000000")
-                )
-        );
+    pi_details.push("Contact:".to_string());
+    if !submission.user.is_empty() {
+        pi_details.push(format!("Email: {}", submission.user));
     }
-    
     if !trl_level.is_empty() {
-        paragraphs.push(
-            Paragraph::new()
-                .indent(Some(500), None, None, None)
-                .add_run(
-                    Run::new()
-                        .add_text(&format!("• TRL Level: {}", trl_level))
-                        .size(28)
-                        .fonts(RunFonts::new().ascii("Calibri"))
-                        .color("#000000")
-                )
-        );
+        pi_details.push(format!("TRL Level: {}", trl_level));
     }
-    
+    blocks.push(Block::List(pi_details));
+
     // Add Co-Project Investigator-1 section
-    paragraphs.push(create_spacing_paragraph());
-    paragraphs.push(create_paragraph_with_bold_heading("6. Name of the Co-Project Investigator-1 (UoQ/TIET):", ""));
-    
-    paragraphs.push(
-        Paragraph::new()
-            .indent(Some(500), None, None, None)
-            .add_run(
-                Run::new()
-                    .add_text("• Designation:")
-                    .size(28)
-                    .fonts(RunFonts::new().ascii("Calibri"))
-                    .color("#000000")
-            )
-    );
-    
-    paragraphs.push(
-        Paragraph::new()
-            .indent(Some(500), None, None, None)
-            .add_run(
-                Run::new()
-                    .add_text("• Department /School")
-                    .size(28)
-                    .fonts(RunFonts::new().ascii("Calibri"))
-                    .color("#000000")
-            )
-    );
-    
-    paragraphs.push(
-        Paragraph::new()
-            .indent(Some(500), None, None, None)
-            .add_run(
-                Run::new()
-                    .add_text("• Area of Specialization")
-                    .size(28)
-                    .fonts(RunFonts::new().ascii("Calibri"))
-                    .color("#000000")
-            )
-    );
-    
-    paragraphs.push(
-        Paragraph::new()
-            .indent(Some(500), None, None, None)
-            .add_run(
-                Run::new()
-                    .add_text("• Date of Joining the Institute")
-                    .size(28)
-                    .fonts(RunFonts::new().ascii("Calibri"))
-                    .color("#000000")
-            )
-    );
-    
-    paragraphs.push(
-        Paragraph::new()
-            .indent(Some(500), None, None, None)
-            .add_run(
-                Run::new()
-                    .add_text("• Date of Award of Ph.D Degree")
-                    .size(28)
-                    .fonts(RunFonts::new().ascii("Calibri"))
-                    .color("#000000")
-            )
-    );
-    
+    blocks.push(Block::Spacer);
+    item(&mut blocks, "6. Name of the Co-Project Investigator-1 (UoQ/TIET)", "");
+    blocks.push(Block::List(investigator_details()));
+
     // Add Co-Project Investigator-2 section if there's a second Co-PI in the submission
-    if let Some(co_pis) = &submission.co_pi {
-        if co_pis.len() > 1 {
-            paragraphs.push(create_spacing_paragraph());
-            paragraphs.push(create_paragraph_with_bold_heading("7. Name of the Co-Project Investigator-2 (UoQ/TIET):", ""));
-            
-            paragraphs.push(
-                Paragraph::new()
-                    .indent(Some(500), None, None, None)
-                    .add_run(
-                        Run::new()
-                            .add_text("• Designation:")
-                            .size(28)
-                            .fonts(RunFonts::new().ascii("Calibri"))
-                            .color("#000000")
-                    )
-            );
-            
-            paragraphs.push(
-                Paragraph::new()
-                    .indent(Some(500), None, None, None)
-                    .add_run(
-                        Run::new()
-                            .add_text("• Department /School")
-                            .size(28)
-                            .fonts(RunFonts::new().ascii("Calibri"))
-                            .color("#000000")
-                    )
-            );
-            
-            paragraphs.push(
-                Paragraph::new()
-                    .indent(Some(500), None, None, None)
-                    .add_run(
-                        Run::new()
-                            .add_text("• Area of Specialization")
-                            .size(28)
-                            .fonts(RunFonts::new().ascii("Calibri"))
-                            .color("#000000")
-                    )
-            );
-            
-            paragraphs.push(
-                Paragraph::new()
-                    .indent(Some(500), None, None, None)
-                    .add_run(
-                        Run::new()
-                            .add_text("• Date of Joining the Institute")
-                            .size(28)
-                            .fonts(RunFonts::new().ascii("Calibri"))
-                            .color("#000000")
-                    )
-            );
-            
-            paragraphs.push(
-                Paragraph::new()
-                    .indent(Some(500), None, None, None)
-                    .add_run(
-                        Run::new()
-                            .add_text("• Date of Award of Ph.D Degree")
-                            .size(28)
-                            .fonts(RunFonts::new().ascii("Calibri"))
-                            .color("#000000")
-                    )
-            );
-        }
+    if submission.co_pi.as_ref().is_some_and(|co_pis| co_pis.len() > 1) {
+        blocks.push(Block::Spacer);
+        item(&mut blocks, "7. Name of the Co-Project Investigator-2 (UoQ/TIET)", "");
+        blocks.push(Block::List(investigator_details()));
     }
-    
-    paragraphs.push(create_spacing_paragraph());
-    
+
+    blocks.push(Block::Spacer);
+
     // Line break before Section B
-    paragraphs.push(Paragraph::new());
-    paragraphs.push(Paragraph::new());
-    
-    // Section B header
-    paragraphs.push(
-        Paragraph::new()
-            .align(AlignmentType::Center)
-            .add_run(
-                Run::new()
-                    .add_text("Section B")
-                    .bold()
-                    .size(48)
-                    .fonts(RunFonts::new().ascii("Arial"))
-                    .color("#000000")
-            )
-    );
-    paragraphs.push(create_spacing_paragraph());
-    
-    // Update the section numbers since we added new sections
-    paragraphs.push(create_paragraph_with_bold_heading("8. Project Title: ", &project_title));
-    paragraphs.push(create_spacing_paragraph());
-    
-    paragraphs.push(create_paragraph_with_bold_heading("9. Project Summary (maximum 500 words): ", &project_summary));
-    paragraphs.push(create_spacing_paragraph());
-    
-    paragraphs.push(create_paragraph_with_bold_heading("10. Keywords: ", &project_keywords));
-    paragraphs.push(create_spacing_paragraph());
-    
-    paragraphs.push(create_paragraph_with_bold_heading("11. Introduction (under the following heads):", ""));
-    paragraphs.push(create_paragraph_with_bold_heading("   11.1 Origin of the proposal: ", &project_origin));
-    paragraphs.push(create_paragraph_with_bold_heading("   11.2 Definition of the problem: ", &problem_definition));
-    paragraphs.push(create_paragraph_with_bold_heading("   11.3 Objective: ", &objectives));
-    paragraphs.push(create_spacing_paragraph());
-    
-    paragraphs.push(create_paragraph_with_bold_heading("12. Review and status of Research and Development in the subject:", ""));
-    paragraphs.push(create_paragraph_with_bold_heading("   12.1 International Status: ", &international_status));
-    paragraphs.push(create_paragraph_with_bold_heading("   12.2 National Status: ", &national_status));
-    paragraphs.push(create_paragraph_with_bold_heading("   12.3 Importance of the proposed project in the context of current status: ", &project_importance));
-    paragraphs.push(create_paragraph_with_bold_heading("   12.4 References: ", &references));
-    paragraphs.push(create_spacing_paragraph());
-    
-    paragraphs.push(create_paragraph_with_bold_heading("13. Work plan:", ""));
-    paragraphs.push(create_paragraph_with_bold_heading("   13.1 Methodology: ", &methodology));
-    paragraphs.push(create_paragraph_with_bold_heading("   13.2 Organization of work elements: ", &work_organization));
-    paragraphs.push(create_paragraph_with_bold_heading("   13.3 Time schedule of activities giving milestones: ", &timeline));
-    paragraphs.push(create_paragraph_with_bold_heading("   13.4 Deliverables: ", &deliverables));
-    paragraphs.push(create_spacing_paragraph());
-    
-    paragraphs.push(create_paragraph_with_bold_heading("14. Facilities available at TIET/UQ: ", &tiet_uq_facilities));
-    paragraphs.push(create_paragraph_with_bold_heading("   14.1 Industry Partner: ", &industry_partner));
-    paragraphs.push(create_paragraph_with_bold_heading("   14.2 Outside TIET/UQ Experts: ", &experts));
-    paragraphs.push(create_paragraph_with_bold_heading("   14.3 Society Impact: ", &society_impact));
-    paragraphs.push(create_spacing_paragraph());
-    
+    blocks.push(Block::Blank);
+    blocks.push(Block::Blank);
+    let section_a = Section::titled("Section A", blocks).new_page();
+
+    let mut blocks = vec![Block::Spacer];
+
+    item(&mut blocks, "8. Project Title", &project_title);
+    blocks.push(Block::Spacer);
+
+    item(&mut blocks, "9. Project Summary (maximum 500 words)", &text(&submission.project_summary));
+    blocks.push(Block::Spacer);
+
+    item(&mut blocks, "10. Keywords", &project_keywords);
+    blocks.push(Block::Spacer);
+
+    item(&mut blocks, "11. Introduction (under the following heads)", "");
+    blocks.push(Block::field("   11.1 Origin of the proposal: ", &text(&submission.project_origin)));
+    blocks.push(Block::field("   11.2 Definition of the problem: ", &text(&submission.problem_definition)));
+    blocks.push(Block::field("   11.3 Objective: ", &format_objectives(submission)));
+    blocks.push(Block::Spacer);
+
+    item(&mut blocks, "12. Review and status of Research and Development in the subject", "");
+    blocks.push(Block::field("   12.1 International Status: ", &text(&submission.international_research_status)));
+    blocks.push(Block::field("   12.2 National Status: ", &text(&submission.national_research_status)));
+    blocks.push(Block::field(
+        "   12.3 Importance of the proposed project in the context of current status: ",
+        &text(&submission.project_importance),
+    ));
+    blocks.push(Block::field("   12.4 References: ", &format_references(submission)));
+    blocks.push(Block::Spacer);
+
+    item(&mut blocks, "13. Work plan", "");
+    blocks.push(Block::field("   13.1 Methodology: ", &text(&submission.methodology)));
+    blocks.push(Block::field("   13.2 Organization of work elements: ", &text(&submission.work_organization)));
+    blocks.push(Block::field("   13.3 Time schedule of activities giving milestones: ", &format_timeline(submission)));
+    blocks.push(Block::field("   13.4 Deliverables: ", &format_deliverables(submission)));
+    blocks.push(Block::Spacer);
+
+    item(&mut blocks, "14. Facilities available at TIET/UQ", &text(&submission.tiet_uq_facilities));
+    blocks.push(Block::field("   14.1 Industry Partner: ", &text(&submission.industry_partner)));
+    blocks.push(Block::field("   14.2 Outside TIET/UQ Experts: ", &format_experts(submission)));
+    blocks.push(Block::field("   14.3 Society Impact: ", &text(&submission.society_impact)));
+    blocks.push(Block::Spacer);
+
     // Add space before budget section
-    paragraphs.push(Paragraph::new());
-    paragraphs.push(
-        Paragraph::new()
-            .add_run(
-                Run::new()
-                    .add_text("15. Budget requirement with justification (Consumables, Equipment, Contingency)")
-                    .bold()
-                    .size(28)
-                    .fonts(RunFonts::new().ascii("Calibri"))
-                    .color("#000000")
-            )
-    );
-    paragraphs.push(create_spacing_paragraph());
-
-    (paragraphs, table)
+    blocks.push(Block::Blank);
+    item(&mut blocks, "15. Budget requirement with justification (Consumables, Equipment, Contingency)", "");
+    blocks.push(Block::Spacer);
+    blocks.push(Block::Table(budget_table(submission)));
+
+    vec![section_a, Section::titled("Section B", blocks)]
+}
+
+// Section A details as (heading, text) pairs, for field-by-field comparison.
+pub fn section_a_fields(submission: &Submission) -> Vec<(&'static str, String)> {
+    let total_months = duration_months(submission);
+    let co_pis = submission
+        .co_pi
+        .as_ref()
+        .map(|co_pis| {
+            co_pis
+                .iter()
+                .map(|co_pi| match &co_pi.role {
+                    Some(role) if !role.is_empty() => format!("{} ({}), {}", co_pi.name, co_pi.email, role),
+                    _ => format!("{} ({})", co_pi.name, co_pi.email),
+                })
+                .collect::<Vec<_>>()
+                .join("; ")
+        })
+        .unwrap_or_default();
+
+    vec![
+        ("2. Sub Area", submission.track.clone()),
+        ("3. Total Cost", calculate_total_budget(submission)),
+        ("4. Duration in months", total_months.to_string()),
+        ("5. E-Code", submission.track_code.as_ref().map(|c| c.to_string()).unwrap_or_default()),
+        ("5. Email", submission.user.clone()),
+        ("5. TRL Level", submission.trl_level.as_ref().map(|t| t.to_string()).unwrap_or_default()),
+        ("6-7. Co-Project Investigators", co_pis),
+    ]
+}
+
+// Section B fields as (heading, text) pairs, numbered as in the template.
+// Used where the proposal is compared field by field.
+pub fn section_b_fields(submission: &Submission) -> Vec<(&'static str, String)> {
+    let text = |value: &Option<String>| value.clone().unwrap_or_default();
+
+    vec![
+        ("8. Project Title", text(&submission.project_title)),
+        ("9. Project Summary", text(&submission.project_summary)),
+        (
+            "10. Keywords",
+            submission.project_keywords.as_ref().map(|k| k.join(", ")).unwrap_or_default(),
+        ),
+        ("11.1 Origin of the proposal", text(&submission.project_origin)),
+        ("11.2 Definition of the problem", text(&submission.problem_definition)),
+        ("11.3 Objective", format_objectives(submission)),
+        ("12.1 International Status", text(&submission.international_research_status)),
+        ("12.2 National Status", text(&submission.national_research_status)),
+        ("12.3 Importance of the proposed project", text(&submission.project_importance)),
+        ("12.4 References", format_references(submission)),
+        ("13.1 Methodology", text(&submission.methodology)),
+        ("13.2 Organization of work elements", text(&submission.work_organization)),
+        ("13.3 Time schedule of activities", format_timeline(submission)),
+        ("13.4 Deliverables", format_deliverables(submission)),
+        ("14. Facilities available at TIET/UQ", text(&submission.tiet_uq_facilities)),
+        ("14.1 Industry Partner", text(&submission.industry_partner)),
+        ("14.2 Outside TIET/UQ Experts", format_experts(submission)),
+        ("14.3 Society Impact", text(&submission.society_impact)),
+        ("16. Any other information", text(&submission.additional_information)),
+    ]
 }
 
-fn calculate_total_budget(submission: &Submission) -> String {
+// Project duration in whole months, counting 30 days as a month
+pub fn duration_months(submission: &Submission) -> i32 {
+    submission.project_duration.as_ref().map_or(0, |duration| {
+        (duration.years * 12) + duration.months + (duration.days as f32 / 30.0).floor() as i32
+    })
+}
+
+// Sum of every budget item's total
+pub fn budget_total(submission: &Submission) -> i64 {
     let mut total = 0;
-    
+
     if let Some(budget_categories) = &submission.budget {
         for category in budget_categories {
             for item in &category.items {
-                total += item.total;
+                total += item.total as i64;
             }
         }
     }
-    
-    total.to_string()
+
+    total
+}
+
+pub fn calculate_total_budget(submission: &Submission) -> String {
+    budget_total(submission).to_string()
 }
 
 fn format_objectives(submission: &Submission) -> String {
@@ -549,7 +283,7 @@ fn format_objectives(submission: &Submission) -> String {
         }
     }
     
-    submission.project_objective_new.as_ref().map_or_else(|| String::new(), |s| s.clone())
+    submission.project_objective_new.as_ref().map_or_else(String::new, |s| s.clone())
 }
 
 fn format_references(submission: &Submission) -> String {
@@ -559,7 +293,7 @@ fn format_references(submission: &Submission) -> String {
         }
     }
     
-    submission.references_new.as_ref().map_or_else(|| String::new(), |s| s.clone())
+    submission.references_new.as_ref().map_or_else(String::new, |s| s.clone())
 }
 
 fn format_timeline(submission: &Submission) -> String {
@@ -569,7 +303,7 @@ fn format_timeline(submission: &Submission) -> String {
         }
     }
     
-    submission.project_timeline_new.as_ref().map_or_else(|| String::new(), |s| s.clone())
+    submission.project_timeline_new.as_ref().map_or_else(String::new, |s| s.clone())
 }
 
 fn format_deliverables(submission: &Submission) -> String {
@@ -579,7 +313,7 @@ fn format_deliverables(submission: &Submission) -> String {
         }
     }
     
-    submission.project_deliverables_new.as_ref().map_or_else(|| String::new(), |s| s.clone())
+    submission.project_deliverables_new.as_ref().map_or_else(String::new, |s| s.clone())
 }
 
 fn format_experts(submission: &Submission) -> String {
@@ -589,36 +323,19 @@ fn format_experts(submission: &Submission) -> String {
         }
     }
     
-    submission.outside_tiet_uq_experts_new.as_ref().map_or_else(|| String::new(), |s| s.clone())
+    submission.outside_tiet_uq_experts_new.as_ref().map_or_else(String::new, |s| s.clone())
 }
 
-#[allow(dead_code)]
-pub fn page2_content_signatures() -> Vec<Paragraph> {
+pub fn page2_content_signatures() -> Vec<Block> {
     vec![
-        Paragraph::new(),
-        Paragraph::new()
-            .add_run(
-                Run::new()
-                    .add_text("16. Any other information which the investigator may like to give in support of his proposal")
-                    .bold()
-                    .size(28)
-                    .fonts(RunFonts::new().ascii("Calibri"))
-                    .color("#000000")  // Changed from #00355F (dark blue) to #000000 (black)
-            ),
-        create_spacing_paragraph(),
-        Paragraph::new(),
-        Paragraph::new(),
-        Paragraph::new()
-            .align(AlignmentType::Left)
-            .add_run(
-                Run::new()
-                    .add_text("Signature of the Applicant")
-                    .size(24)
-                    .fonts(RunFonts::new().ascii("Georgia"))
-                    .color("#333333")
-            ),
-        Paragraph::new(),
-        Paragraph::new(),
-        Paragraph::new(),
+        Block::Blank,
+        Block::Heading("16. Any other information which the investigator may like to give in support of his proposal".to_string()),
+        Block::Spacer,
+        Block::Blank,
+        Block::Blank,
+        Block::Paragraph(Paragraph::styled(StyleName::Signature).text("Signature of the Applicant", TextStyle::new())),
+        Block::Blank,
+        Block::Blank,
+        Block::Blank,
     ]
 }
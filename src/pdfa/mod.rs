@@ -0,0 +1,172 @@
+// Conformance check for PDF/A-2b renders. LibreOffice writes plain
+// (uncompressed) object dictionaries and XMP metadata, so the checks are
+// byte searches rather than a full PDF parse. They cover what the records
+// office relies on: PDF/A-2b identification in the XMP metadata, an output
+// intent, embedded fonts and no encryption.
+
+// Position of `needle` in `haystack` at or after `from`
+pub fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|position| position + from)
+}
+
+fn contains(haystack: &[u8], needle: &str) -> bool {
+    find(haystack, needle.as_bytes(), 0).is_some()
+}
+
+// The XMP value of `property` as either an element or an attribute
+fn xmp_value(xmp: &str, property: &str) -> Option<String> {
+    let element = format!("<{}>", property);
+    if let Some(start) = xmp.find(&element) {
+        let rest = &xmp[start + element.len()..];
+        return rest.find('<').map(|end| rest[..end].trim().to_string());
+    }
+    let attribute = format!("{}=\"", property);
+    let start = xmp.find(&attribute)? + attribute.len();
+    let rest = &xmp[start..];
+    rest.find('"').map(|end| rest[..end].to_string())
+}
+
+// Number of font descriptors without an embedded font program
+pub fn unembedded_fonts(pdf: &[u8]) -> usize {
+    let mut count = 0;
+    let mut from = 0;
+    while let Some(position) = find(pdf, b"/FontDescriptor", from) {
+        from = position + 1;
+        // the reference from a font dictionary is "/FontDescriptor 12 0 R"
+        if !pdf[..position].ends_with(b"/Type") && !pdf[..position].ends_with(b"/Type ") {
+            continue;
+        }
+        let start = pdf[..position]
+            .windows(3)
+            .rposition(|window| window == b"obj")
+            .unwrap_or(0);
+        let end = find(pdf, b"endobj", position).unwrap_or(pdf.len());
+        if find(&pdf[start..end], b"/FontFile", 0).is_none() {
+            count += 1;
+        }
+    }
+    count
+}
+
+// Problems that keep `pdf` from being PDF/A-2b; empty when it conforms.
+pub fn check(pdf: &[u8]) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if !pdf.starts_with(b"%PDF-") {
+        problems.push("not a PDF file".to_string());
+        return problems;
+    }
+    if contains(pdf, "/Encrypt") {
+        problems.push("document is encrypted".to_string());
+    }
+
+    let xmp = find(pdf, b"<x:xmpmeta", 0).and_then(|start| {
+        let end = find(pdf, b"</x:xmpmeta>", start)?;
+        Some(String::from_utf8_lossy(&pdf[start..end]).into_owned())
+    });
+    match xmp {
+        None => problems.push("no XMP metadata".to_string()),
+        Some(xmp) => {
+            let part = xmp_value(&xmp, "pdfaid:part");
+            let conformance = xmp_value(&xmp, "pdfaid:conformance");
+            if part.as_deref() != Some("2") || !conformance.as_deref().is_some_and(|c| c.eq_ignore_ascii_case("B")) {
+                problems.push(format!(
+                    "XMP metadata identifies PDF/A part {} conformance {}, expected 2B",
+                    part.as_deref().unwrap_or("none"),
+                    conformance.as_deref().unwrap_or("none")
+                ));
+            }
+        }
+    }
+
+    if !contains(pdf, "/OutputIntents") || !contains(pdf, "/GTS_PDFA1") {
+        problems.push("no PDF/A output intent".to_string());
+    }
+
+    let unembedded = unembedded_fonts(pdf);
+    if unembedded > 0 {
+        problems.push(format!("{} font(s) not embedded", unembedded));
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Skeleton of a LibreOffice PDF/A-2b export: catalog with output
+    // intent, XMP metadata, and a TrueType font with its descriptor
+    const XMP: &str = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"><rdf:Description rdf:about="" xmlns:pdfaid="http://www.aiim.org/pdfa/ns/id/"><pdfaid:part>2</pdfaid:part><pdfaid:conformance>B</pdfaid:conformance></rdf:Description></rdf:RDF></x:xmpmeta>"#;
+    const CATALOG: &str = "1 0 obj\n<</Type/Catalog/Pages 2 0 R/Metadata 3 0 R/OutputIntents[<</Type/OutputIntent/S/GTS_PDFA1/OutputConditionIdentifier(sRGB IEC61966-2.1)>>]>>\nendobj\n";
+    const FONT: &str = "5 0 obj\n<</Type/Font/Subtype/TrueType/BaseFont/BAAAAA+Carlito/FontDescriptor 6 0 R>>\nendobj\n";
+    const EMBEDDED_DESCRIPTOR: &str = "6 0 obj\n<</Type/FontDescriptor/FontName/BAAAAA+Carlito/Flags 4/FontFile2 7 0 R>>\nendobj\n";
+    const BARE_DESCRIPTOR: &str = "8 0 obj\n<</Type /FontDescriptor/FontName/Helvetica/Flags 32>>\nendobj\n";
+
+    fn pdf(parts: &[&str]) -> Vec<u8> {
+        let mut pdf = String::from("%PDF-1.7\n");
+        for part in parts {
+            pdf.push_str(part);
+        }
+        pdf.push_str("%%EOF\n");
+        pdf.into_bytes()
+    }
+
+    fn metadata(xmp: &str) -> String {
+        format!("3 0 obj\n<</Type/Metadata/Subtype/XML/Length {}>>\nstream\n{}\nendstream\nendobj\n", xmp.len(), xmp)
+    }
+
+    #[test]
+    fn accepts_a_conforming_pdf() {
+        let conforming = pdf(&[CATALOG, &metadata(XMP), FONT, EMBEDDED_DESCRIPTOR]);
+
+        assert_eq!(check(&conforming), Vec::<String>::new());
+        assert_eq!(unembedded_fonts(&conforming), 0);
+    }
+
+    #[test]
+    fn reads_the_identification_from_attributes() {
+        let xmp = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:Description pdfaid:part="2" pdfaid:conformance="b"/></x:xmpmeta>"#;
+        let conforming = pdf(&[CATALOG, &metadata(xmp), FONT, EMBEDDED_DESCRIPTOR]);
+
+        assert!(check(&conforming).is_empty());
+    }
+
+    #[test]
+    fn counts_fonts_without_a_font_program() {
+        let partly_embedded = pdf(&[CATALOG, &metadata(XMP), FONT, EMBEDDED_DESCRIPTOR, BARE_DESCRIPTOR]);
+
+        assert_eq!(unembedded_fonts(&partly_embedded), 1);
+        assert_eq!(check(&partly_embedded), ["1 font(s) not embedded"]);
+    }
+
+    #[test]
+    fn reports_every_problem_of_a_plain_pdf() {
+        let catalog = "1 0 obj\n<</Type/Catalog/Pages 2 0 R>>\nendobj\n";
+        let trailer = "trailer\n<</Root 1 0 R/Encrypt 9 0 R>>\n";
+        let plain = pdf(&[catalog, FONT, BARE_DESCRIPTOR, trailer]);
+
+        assert_eq!(
+            check(&plain),
+            [
+                "document is encrypted",
+                "no XMP metadata",
+                "no PDF/A output intent",
+                "1 font(s) not embedded",
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_other_pdfa_parts() {
+        let xmp = XMP.replace("<pdfaid:part>2</pdfaid:part>", "<pdfaid:part>1</pdfaid:part>");
+        let pdfa1 = pdf(&[CATALOG, &metadata(&xmp), FONT, EMBEDDED_DESCRIPTOR]);
+
+        assert_eq!(check(&pdfa1), ["XMP metadata identifies PDF/A part 1 conformance B, expected 2B"]);
+        assert_eq!(check(b"<html></html>"), ["not a PDF file"]);
+    }
+}
@@ -0,0 +1,497 @@
+use crate::document::{format_date, Block, Cell, Document, Paragraph, Section, StyleName, StyleSheet, Table, TextStyle};
+use crate::models::Submission;
+use crate::sanction::rupees;
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+// Reports of funded projects: the half-yearly progress report and the
+// utilisation certificate. Both take the original submission, whose budget
+// is the plan, and an `Expenditure` with what was actually spent on each
+// budget item per year, and compare the two in variance tables per budget
+// category and year. Only the years the expenditure covers are compared,
+// so later years' plans don't show up as underspending.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportKind {
+    Progress,
+    Utilisation,
+}
+
+impl ReportKind {
+    pub fn title(self) -> &'static str {
+        match self {
+            ReportKind::Progress => "Progress Report",
+            ReportKind::Utilisation => "Utilisation Certificate",
+        }
+    }
+
+    pub fn filename(self, submission: &Submission, extension: &str) -> String {
+        let kind = match self {
+            ReportKind::Progress => "progress_report",
+            ReportKind::Utilisation => "utilisation_certificate",
+        };
+        format!("{}_{}.{}", kind, submission.unique_id, extension)
+    }
+}
+
+// Spending on one budget item, in rupees per project year to date
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct ItemSpending {
+    // The budget category's type, e.g. "Recurring"
+    pub category: String,
+    // The budget item's id or heading
+    pub item: String,
+    pub years: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct Expenditure {
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    #[serde(default)]
+    pub items: Vec<ItemSpending>,
+    // Grant released to the project so far; defaults to the planned amount
+    // of the years covered
+    #[serde(default)]
+    pub grant_received: Option<i64>,
+    // Reference number of the sanction letter
+    #[serde(default)]
+    pub sanction_reference: Option<String>,
+    // Progress report narrative
+    #[serde(default)]
+    pub work_done: Option<String>,
+    #[serde(default)]
+    pub deviations: Option<String>,
+    #[serde(default)]
+    pub next_period_plan: Option<String>,
+}
+
+impl Expenditure {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.period_end < self.period_start {
+            return Err("periodEnd is before periodStart".to_string());
+        }
+        if let Some(spending) = self.items.iter().find(|spending| spending.years.iter().any(|amount| *amount < 0)) {
+            return Err(format!("negative amount spent on {:?}", spending.item));
+        }
+        if self.grant_received.is_some_and(|amount| amount < 0) {
+            return Err("grantReceived can't be negative".to_string());
+        }
+        Ok(())
+    }
+}
+
+// Body of POST /submissions/progress-report and
+// POST /submissions/utilisation-certificate
+#[derive(Deserialize)]
+pub struct ReportRequest {
+    pub submission: Submission,
+    pub expenditure: Expenditure,
+}
+
+// Planned and actual amounts of one budget item, per year
+pub struct VarianceRow {
+    pub item: String,
+    pub planned: Vec<i64>,
+    pub actual: Vec<i64>,
+}
+
+pub struct CategoryVariance {
+    pub category: String,
+    pub rows: Vec<VarianceRow>,
+}
+
+impl CategoryVariance {
+    fn planned(&self, year: usize) -> i64 {
+        self.rows.iter().map(|row| row.planned[year]).sum()
+    }
+
+    fn actual(&self, year: usize) -> i64 {
+        self.rows.iter().map(|row| row.actual[year]).sum()
+    }
+}
+
+pub struct Variance {
+    pub years: usize,
+    pub categories: Vec<CategoryVariance>,
+}
+
+fn same(a: &str, b: &str) -> bool {
+    a.trim().eq_ignore_ascii_case(b.trim())
+}
+
+fn amounts(values: impl Iterator<Item = i64>, years: usize) -> Vec<i64> {
+    let mut amounts: Vec<i64> = values.take(years).collect();
+    amounts.resize(years, 0);
+    amounts
+}
+
+impl Variance {
+    // Matches spending to the budget by category and item id or heading.
+    // Each budget item first takes one entry of its own, so items sharing a
+    // heading split their entries in order; further entries for a planned
+    // item add to it. Spending on items that aren't in the budget is listed
+    // as unplanned.
+    pub fn new(submission: &Submission, expenditure: &Expenditure) -> Self {
+        let years = expenditure
+            .items
+            .iter()
+            .map(|spending| spending.years.len())
+            .max()
+            .unwrap_or(0)
+            .max(1);
+
+        let mut categories: Vec<CategoryVariance> = Vec::new();
+        // (category, row) of every budget item, with its category type, id and heading
+        let mut planned = Vec::new();
+        for category in submission.budget.iter().flatten() {
+            let rows = category
+                .items
+                .iter()
+                .enumerate()
+                .map(|(row, item)| {
+                    planned.push((categories.len(), row, &category.category_type, &item.id, &item.heading));
+                    VarianceRow {
+                        item: item.heading.clone(),
+                        planned: amounts(item.years.iter().map(|amount| *amount as i64), years),
+                        actual: vec![0; years],
+                    }
+                })
+                .collect();
+            categories.push(CategoryVariance {
+                category: category.category_type.clone(),
+                rows,
+            });
+        }
+
+        let matches = |spending: &ItemSpending, category: &str, id: &str, heading: &str| {
+            same(&spending.category, category) && (same(&spending.item, id) || same(&spending.item, heading))
+        };
+        let mut owner: Vec<Option<(usize, usize)>> = vec![None; expenditure.items.len()];
+        for &(category, row, category_type, id, heading) in &planned {
+            let unclaimed = expenditure
+                .items
+                .iter()
+                .enumerate()
+                .find(|(index, spending)| owner[*index].is_none() && matches(spending, category_type, id, heading));
+            if let Some((index, _)) = unclaimed {
+                owner[index] = Some((category, row));
+            }
+        }
+        for (index, spending) in expenditure.items.iter().enumerate() {
+            if owner[index].is_none() {
+                owner[index] = planned
+                    .iter()
+                    .find(|(_, _, category_type, id, heading)| matches(spending, category_type, id, heading))
+                    .map(|&(category, row, ..)| (category, row));
+            }
+        }
+
+        for (spending, owner) in expenditure.items.iter().zip(owner) {
+            let actual = amounts(spending.years.iter().copied(), years);
+            if let Some((category, row)) = owner {
+                let row = &mut categories[category].rows[row];
+                for (total, amount) in row.actual.iter_mut().zip(actual) {
+                    *total += amount;
+                }
+                continue;
+            }
+            let row = VarianceRow {
+                item: format!("{} (unplanned)", spending.item),
+                planned: vec![0; years],
+                actual,
+            };
+            match categories.iter_mut().find(|category| same(&category.category, &spending.category)) {
+                Some(category) => category.rows.push(row),
+                None => categories.push(CategoryVariance {
+                    category: spending.category.clone(),
+                    rows: vec![row],
+                }),
+            }
+        }
+
+        Self { years, categories }
+    }
+
+    pub fn planned_total(&self) -> i64 {
+        self.categories.iter().map(|category| (0..self.years).map(|year| category.planned(year)).sum::<i64>()).sum()
+    }
+
+    pub fn actual_total(&self) -> i64 {
+        self.categories.iter().map(|category| (0..self.years).map(|year| category.actual(year)).sum::<i64>()).sum()
+    }
+}
+
+// Share of the planned amount spent, e.g. "85.0%"
+fn utilised(planned: i64, actual: i64) -> String {
+    if planned == 0 {
+        "-".to_string()
+    } else {
+        format!("{:.1}%", actual as f64 * 100.0 / planned as f64)
+    }
+}
+
+fn amount_cells(planned: i64, actual: i64, strong: bool) -> Vec<Cell> {
+    let cell = |text: String| if strong { Cell::bold(&text) } else { Cell::new(text) };
+    vec![
+        cell(rupees(planned)),
+        cell(rupees(actual)),
+        cell(rupees(planned - actual)),
+        cell(utilised(planned, actual)),
+    ]
+}
+
+fn variance_header(first: &str, second: &str) -> Vec<Cell> {
+    vec![
+        Cell::bold(first).width(2300),
+        Cell::bold(second).width(900),
+        Cell::bold("Planned").width(1400),
+        Cell::bold("Actual").width(1400),
+        Cell::bold("Variance").width(1400),
+        Cell::bold("Utilised").width(1100),
+    ]
+}
+
+// Every item of the category per year, then the category's total per year
+// and overall
+fn category_table(category: &CategoryVariance, years: usize) -> Table {
+    let mut rows = vec![variance_header("Item", "Year")];
+    for row in &category.rows {
+        for year in 0..years {
+            let item = if year == 0 { Cell::new(&row.item) } else { Cell::empty() };
+            let mut cells = vec![item, Cell::new(format!("Year {}", year + 1))];
+            cells.extend(amount_cells(row.planned[year], row.actual[year], false));
+            rows.push(cells);
+        }
+    }
+    // per-year totals only add to the overall total for several years
+    for year in (0..years).filter(|_| years > 1) {
+        let label = if year == 0 { Cell::bold("Category total") } else { Cell::empty() };
+        let mut cells = vec![label, Cell::bold(&format!("Year {}", year + 1))];
+        cells.extend(amount_cells(category.planned(year), category.actual(year), true));
+        rows.push(cells);
+    }
+    let planned: i64 = (0..years).map(|year| category.planned(year)).sum();
+    let actual: i64 = (0..years).map(|year| category.actual(year)).sum();
+    let mut cells = vec![Cell::bold("Total"), Cell::empty()];
+    cells.extend(amount_cells(planned, actual, true));
+    rows.push(cells);
+    Table { rows }
+}
+
+// One line per category and year, then the grand total
+fn summary_table(variance: &Variance) -> Table {
+    let mut rows = vec![variance_header("Category", "Year")];
+    for category in &variance.categories {
+        for year in 0..variance.years {
+            let label = if year == 0 { Cell::new(&category.category) } else { Cell::empty() };
+            let mut cells = vec![label, Cell::new(format!("Year {}", year + 1))];
+            cells.extend(amount_cells(category.planned(year), category.actual(year), false));
+            rows.push(cells);
+        }
+    }
+    let mut cells = vec![Cell::bold("Grand total"), Cell::empty()];
+    cells.extend(amount_cells(variance.planned_total(), variance.actual_total(), true));
+    rows.push(cells);
+    Table { rows }
+}
+
+fn variance_blocks(variance: &Variance, blocks: &mut Vec<Block>) {
+    blocks.push(Block::Text(
+        "Variance is the planned amount less the amount spent; a negative variance is overspending.".to_string(),
+    ));
+    for category in &variance.categories {
+        blocks.push(Block::Paragraph(
+            Paragraph::new().text(&category.category, TextStyle::new().bold()),
+        ));
+        blocks.push(Block::Table(category_table(category, variance.years)));
+        blocks.push(Block::Blank);
+    }
+    blocks.push(Block::Paragraph(Paragraph::new().text("All categories", TextStyle::new().bold())));
+    blocks.push(Block::Table(summary_table(variance)));
+}
+
+fn project_fields(submission: &Submission, expenditure: &Expenditure, blocks: &mut Vec<Block>) {
+    blocks.push(Block::field("Unique ID: ", &submission.unique_id));
+    blocks.push(Block::field("Project Title: ", submission.project_title.as_deref().unwrap_or_default()));
+    blocks.push(Block::field("Principal Investigator: ", &submission.user));
+    if let Some(reference) = &expenditure.sanction_reference {
+        blocks.push(Block::field("Sanction Reference: ", reference));
+    }
+    blocks.push(Block::field(
+        "Reporting Period: ",
+        &format!("{} to {}", format_date(expenditure.period_start), format_date(expenditure.period_end)),
+    ));
+}
+
+fn narrative(heading: &str, text: &Option<String>, blocks: &mut Vec<Block>) {
+    blocks.push(Block::Heading(heading.to_string()));
+    match text.as_deref().map(str::trim).filter(|text| !text.is_empty()) {
+        Some(text) => blocks.push(Block::Text(text.to_string())),
+        None => blocks.extend(vec![Block::Blank; 3]),
+    }
+}
+
+fn signature(text: &str) -> Block {
+    Block::Paragraph(Paragraph::styled(StyleName::Signature).text(text, TextStyle::new()))
+}
+
+fn progress_report(submission: &Submission, expenditure: &Expenditure, variance: &Variance) -> Vec<Block> {
+    let mut blocks = vec![Block::Heading("1. Project Details".to_string())];
+    project_fields(submission, expenditure, &mut blocks);
+
+    blocks.push(Block::Spacer);
+    blocks.push(Block::Heading("2. Objectives as Proposed".to_string()));
+    match submission.project_objective.as_ref().filter(|objectives| !objectives.is_empty()) {
+        Some(objectives) => blocks.push(Block::List(objectives.clone())),
+        None => blocks.push(Block::Text(submission.project_objective_new.clone().unwrap_or_default())),
+    }
+
+    blocks.push(Block::Spacer);
+    narrative("3. Work Done during the Period", &expenditure.work_done, &mut blocks);
+    blocks.push(Block::Spacer);
+    narrative("4. Deviations from the Work Plan", &expenditure.deviations, &mut blocks);
+    blocks.push(Block::Spacer);
+    narrative("5. Plan for the Next Period", &expenditure.next_period_plan, &mut blocks);
+
+    blocks.push(Block::Spacer);
+    blocks.push(Block::Heading("6. Expenditure against Budget".to_string()));
+    variance_blocks(variance, &mut blocks);
+
+    blocks.extend(vec![Block::Blank; 3]);
+    blocks.push(signature("Signature of the Principal Investigator"));
+    blocks
+}
+
+fn utilisation_certificate(submission: &Submission, expenditure: &Expenditure, variance: &Variance) -> Vec<Block> {
+    let received = expenditure.grant_received.unwrap_or_else(|| variance.planned_total());
+    let spent = variance.actual_total();
+    let title = submission.project_title.as_deref().unwrap_or(&submission.unique_id);
+    let sanction = expenditure
+        .sanction_reference
+        .as_ref()
+        .map_or_else(String::new, |reference| format!(" under sanction letter No. {}", reference));
+
+    let mut blocks = vec![Block::Heading("Project Details".to_string())];
+    project_fields(submission, expenditure, &mut blocks);
+    blocks.push(Block::Spacer);
+    blocks.push(Block::Heading("Certificate".to_string()));
+    blocks.push(Block::Text(format!(
+        "Certified that out of {} of grant received for the project \"{}\" ({}){}, a sum of {} has been utilised \
+         up to {} for the purpose for which it was sanctioned, and that the balance of {} remains unutilised.",
+        rupees(received),
+        title,
+        submission.unique_id,
+        sanction,
+        rupees(spent),
+        format_date(expenditure.period_end),
+        rupees(received - spent),
+    )));
+    blocks.push(Block::Text(
+        "Certified that the expenditure has been incurred in accordance with the terms and conditions of the \
+         sanction, and that the items and amounts below have been checked against the accounts of the project."
+            .to_string(),
+    ));
+
+    blocks.push(Block::Spacer);
+    blocks.push(Block::Heading("Statement of Expenditure".to_string()));
+    let mut rows = vec![vec![Cell::bold("Grant received").width(4500), Cell::new(rupees(received)).width(4000)]];
+    rows.push(vec![Cell::bold("Expenditure"), Cell::new(rupees(spent))]);
+    rows.push(vec![Cell::bold("Unspent balance"), Cell::bold(&rupees(received - spent))]);
+    blocks.push(Block::Table(Table { rows }));
+    blocks.push(Block::Blank);
+    variance_blocks(variance, &mut blocks);
+
+    blocks.extend(vec![Block::Blank; 3]);
+    blocks.push(signature("Principal Investigator"));
+    blocks.extend(vec![Block::Blank; 2]);
+    blocks.push(signature("Accounts Officer"));
+    blocks.extend(vec![Block::Blank; 2]);
+    blocks.push(signature("Head, COE-DSAI"));
+    blocks
+}
+
+pub fn report(kind: ReportKind, submission: &Submission, expenditure: &Expenditure, styles: &StyleSheet) -> Document {
+    let variance = Variance::new(submission, expenditure);
+    let mut blocks = vec![
+        Block::Paragraph(Paragraph::styled(StyleName::CoverText).text("COE-DSAI Seed Grant", TextStyle::new())),
+        Block::Spacer,
+    ];
+    blocks.extend(match kind {
+        ReportKind::Progress => progress_report(submission, expenditure, &variance),
+        ReportKind::Utilisation => utilisation_certificate(submission, expenditure, &variance),
+    });
+
+    Document {
+        title: format!("{}: {}", kind.title(), submission.unique_id),
+        styles: styles.clone(),
+        sections: vec![Section::titled(kind.title(), blocks)],
+        landscape: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::BudgetItem;
+
+    fn row<'a>(variance: &'a Variance, category: &str, item: &str) -> &'a VarianceRow {
+        let category = variance.categories.iter().find(|c| c.category == category).unwrap();
+        category.rows.iter().find(|row| row.item == item).unwrap()
+    }
+
+    #[test]
+    fn compares_spending_with_the_budget_per_category_and_year() {
+        let mut submission: Submission = serde_json::from_str(include_str!("../../mock_submission.json")).unwrap();
+        let recurring = &mut submission.budget.as_mut().unwrap()[0];
+        recurring.items.push(BudgetItem {
+            heading: "Travel".to_string(),
+            id: "travel-abroad".to_string(),
+            is_required: false,
+            justification: String::new(),
+            total: 10000,
+            years: vec![10000, 0, 0],
+        });
+        let expenditure: Expenditure = serde_json::from_value(serde_json::json!({
+            "periodStart": "2025-04-01",
+            "periodEnd": "2026-09-30",
+            "items": [
+                { "category": "Recurring", "item": "manpower", "years": [100000, 50000] },
+                { "category": "Recurring", "item": "Manpower", "years": [20000] },
+                { "category": "Recurring", "item": "Travel", "years": [30000, 5000] },
+                { "category": "Recurring", "item": "travel", "years": [8000] },
+                { "category": "Recurring", "item": "Printing", "years": [5000, 0] },
+                { "category": "Non-Recurring", "item": "gpu", "years": [140000] },
+                { "category": "Overheads", "item": "Audit", "years": [0, 12000] }
+            ]
+        }))
+        .unwrap();
+
+        let variance = Variance::new(&submission, &expenditure);
+        assert_eq!(variance.years, 2);
+
+        // a second entry for a planned item adds to it
+        assert_eq!(row(&variance, "Recurring", "Manpower").actual, vec![120000, 50000]);
+        // items sharing a heading each take one entry
+        let travel: Vec<_> = variance.categories[0].rows.iter().filter(|row| row.item == "Travel").collect();
+        assert_eq!(travel[0].actual, vec![30000, 5000]);
+        assert_eq!(travel[1].actual, vec![8000, 0]);
+
+        let printing = row(&variance, "Recurring", "Printing (unplanned)");
+        assert_eq!((printing.planned.clone(), printing.actual.clone()), (vec![0, 0], vec![5000, 0]));
+        let audit = row(&variance, "Overheads", "Audit (unplanned)");
+        assert_eq!((audit.planned.clone(), audit.actual.clone()), (vec![0, 0], vec![0, 12000]));
+
+        let totals = |category: usize| {
+            let category = &variance.categories[category];
+            (0..2).map(|year| (category.planned(year), category.actual(year))).collect::<Vec<_>>()
+        };
+        assert_eq!(totals(0), vec![(290000, 163000), (250000, 55000)]);
+        assert_eq!(totals(1), vec![(500000, 140000), (0, 0)]);
+        assert_eq!(totals(2), vec![(0, 0), (0, 12000)]);
+        assert_eq!(variance.planned_total(), 1040000);
+        assert_eq!(variance.actual_total(), 370000);
+    }
+}
@@ -0,0 +1,74 @@
+use crate::models::Submission;
+use crate::utils::percent_encode;
+use chrono::{DateTime, Utc};
+use image::{ImageFormat, Luma};
+use qrcode::{EcLevel, QrCode};
+use std::io::Cursor;
+
+// Verification QR code for the cover page. It encodes the submission's
+// unique_id, when the document was generated and the submission's content
+// hash, so a printed copy can be matched to the stored version it was
+// generated from (the versions listing shows each version's hash). With a
+// verification URL prefix the code is a link,
+// `<prefix><unique_id>?generated=...&hash=...`; without one it is plain text.
+
+// Pixels per module of the PNG; documents scale it to the display size
+const MODULE_PX: u32 = 8;
+
+#[derive(Debug, Clone)]
+pub struct CoverCode {
+    pub unique_id: String,
+    pub generated_at: DateTime<Utc>,
+    pub content_hash: String,
+}
+
+impl CoverCode {
+    pub fn new(submission: &Submission, generated_at: DateTime<Utc>) -> Self {
+        Self {
+            unique_id: submission.unique_id.clone(),
+            generated_at,
+            content_hash: submission.content_hash(),
+        }
+    }
+
+    // Text encoded in the QR code
+    pub fn payload(&self, url_prefix: Option<&str>) -> String {
+        let generated = self.generated_at.format("%Y-%m-%dT%H:%M:%SZ");
+        match url_prefix {
+            Some(prefix) => format!(
+                "{}{}?generated={}&hash={}",
+                prefix,
+                percent_encode(&self.unique_id),
+                generated,
+                self.content_hash
+            ),
+            None => format!(
+                "unique_id: {}\ngenerated: {}\nsha256: {}",
+                self.unique_id, generated, self.content_hash
+            ),
+        }
+    }
+}
+
+// PNG of the QR code for `payload`, None if it can't be encoded
+pub fn png(payload: &str) -> Option<Vec<u8>> {
+    // medium error correction survives a fold or a smudge on the printout
+    let code = match QrCode::with_error_correction_level(payload, EcLevel::M) {
+        Ok(code) => code,
+        Err(e) => {
+            println!("Failed to encode QR code: {}", e);
+            return None;
+        }
+    };
+    let image = code
+        .render::<Luma<u8>>()
+        .module_dimensions(MODULE_PX, MODULE_PX)
+        .build();
+
+    let mut png = Cursor::new(Vec::new());
+    if let Err(e) = image.write_to(&mut png, ImageFormat::Png) {
+        println!("Failed to write QR code image: {}", e);
+        return None;
+    }
+    Some(png.into_inner())
+}
@@ -0,0 +1,397 @@
+use crate::config::Config;
+use crate::decorations::{apply_watermark, page_footer, page_header, resolve_watermark};
+use crate::models::Submission;
+use crate::document::{self, StyleSheet};
+use crate::evaluation;
+use crate::metadata::{self, DocumentInfo};
+use crate::pdfa;
+use crate::progress::{self, Expenditure, ReportKind};
+use crate::sanction::{self, Approval};
+use crate::signing;
+use crate::summary::{self, CommitteeSummary};
+use crate::utils;
+use chrono::{Local, Utc};
+use docx_rs::{Footer, Header, XMLDocx};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Per-request rendering switches, read from the query string.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct RenderOptions {
+    // Watermark text overriding the status-based default; "none" disables it
+    pub watermark: Option<String>,
+    pub header: Option<bool>,
+    pub footer: Option<bool>,
+    // PDF/A-2b instead of a regular PDF
+    pub pdfa: Option<bool>,
+    // Contents page after the cover
+    pub contents: Option<bool>,
+    // Verification QR code on the cover
+    pub qr: Option<bool>,
+    // Branding profile instead of the configured default
+    pub branding: Option<String>,
+    // PAdES-sign the PDF (default: SIGN_PDF)
+    pub sign: Option<bool>,
+}
+
+// Format of the reports and letters rendered besides the proposal, from
+// `?format=`. Case-insensitive; anything else is rejected with a 400.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum DocumentFormat {
+    #[default]
+    Pdf,
+    Docx,
+    Html,
+}
+
+impl TryFrom<String> for DocumentFormat {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.to_ascii_lowercase().as_str() {
+            "pdf" => Ok(DocumentFormat::Pdf),
+            "docx" => Ok(DocumentFormat::Docx),
+            "html" => Ok(DocumentFormat::Html),
+            _ => Err(format!("Unsupported format \"{}\"; expected pdf, docx or html", value)),
+        }
+    }
+}
+
+impl DocumentFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            DocumentFormat::Pdf => "pdf",
+            DocumentFormat::Docx => "docx",
+            DocumentFormat::Html => "html",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            DocumentFormat::Pdf => "application/pdf",
+            DocumentFormat::Docx => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            DocumentFormat::Html => "text/html; charset=utf-8",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RenderError {
+    OutputDir(io::Error),
+    CreateDocx(io::Error),
+    PackDocx(String),
+    Convert(io::Error),
+    ReadPdf(io::Error),
+    // PDF/A was requested but the output doesn't conform
+    Conformance(String),
+    // The request named a branding profile that isn't loaded
+    UnknownBranding(String),
+    // Signing was asked for but no certificate is configured
+    SigningUnavailable,
+    Sign(String),
+}
+
+impl RenderError {
+    // Short message safe to return to API clients
+    pub fn message(&self) -> &'static str {
+        match self {
+            RenderError::OutputDir(_) => "Failed to create output directory",
+            RenderError::CreateDocx(_) => "Failed to create DOCX file",
+            RenderError::PackDocx(_) => "Failed to create DOCX document",
+            RenderError::Convert(_) => "Failed to generate PDF",
+            RenderError::ReadPdf(_) => "Failed to read PDF file",
+            RenderError::Conformance(_) => "Generated PDF is not PDF/A-2b conformant",
+            RenderError::UnknownBranding(_) => "Unknown branding profile",
+            RenderError::SigningUnavailable => "PDF signing is not configured",
+            RenderError::Sign(_) => "Failed to sign PDF",
+        }
+    }
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderError::OutputDir(e)
+            | RenderError::CreateDocx(e)
+            | RenderError::Convert(e)
+            | RenderError::ReadPdf(e) => write!(f, "{}: {}", self.message(), e),
+            RenderError::PackDocx(e)
+            | RenderError::Conformance(e)
+            | RenderError::UnknownBranding(e)
+            | RenderError::Sign(e) => write!(f, "{}: {}", self.message(), e),
+            RenderError::SigningUnavailable => write!(f, "{}", self.message()),
+        }
+    }
+}
+
+// The branding profile's style sheet for this request
+pub fn style_sheet<'a>(config: &'a Config, options: &RenderOptions) -> Result<&'a StyleSheet, RenderError> {
+    let profile = options.branding.as_deref();
+    config
+        .branding
+        .style_sheet(profile)
+        .ok_or_else(|| RenderError::UnknownBranding(profile.unwrap_or_default().to_string()))
+}
+
+// Whether this request's PDF gets signed
+pub fn signing_enabled(config: &Config, options: &RenderOptions) -> bool {
+    options.sign.unwrap_or(config.signing.sign_by_default)
+}
+
+pub fn pdf_filename(submission: &Submission) -> String {
+    format!("proposal_{}.pdf", submission.unique_id)
+}
+
+// Builds the complete proposal (cover, sections A/B, budget table,
+// signatures, headers/footers, watermark and document properties) ready to
+// be packed.
+pub fn build_docx(submission: &Submission, config: &Config, options: &RenderOptions) -> Result<XMLDocx, RenderError> {
+    let contents = options.contents.unwrap_or(config.template.table_of_contents);
+    let qr = options.qr.unwrap_or(config.template.cover_qr);
+    let styles = style_sheet(config, options)?;
+    let proposal = document::proposal(submission, &config.template, contents, qr, styles);
+    let mut doc = document::docx::to_docx(&proposal);
+    doc = metadata::add_custom_properties(doc, submission);
+
+    // headers, footers and watermark
+    let show_header = options.header.unwrap_or(config.template.show_header);
+    let show_footer = options.footer.unwrap_or(config.template.show_footer);
+    let watermark = resolve_watermark(submission, &config.template, options.watermark.as_deref());
+
+    // the watermark lives in the header parts, so they are needed even when
+    // the running header itself is turned off
+    if show_header || watermark.is_some() {
+        let header = if show_header { page_header(submission) } else { Header::new() };
+        doc = doc.header(header).first_header(Header::new());
+    }
+    if show_footer {
+        doc = doc.footer(page_footer(Local::now())).first_footer(Footer::new());
+    }
+
+    let mut xml = doc.build();
+    if let Some(text) = &watermark {
+        apply_watermark(&mut xml, text);
+    }
+    metadata::apply_core_properties(&mut xml, &DocumentInfo::from_submission(submission));
+    Ok(xml)
+}
+
+// Reviewer evaluation form for the proposal, with the proposal's running
+// header and footer and branding. Forms are left unsigned so reviewers can
+// fill them in.
+pub fn build_evaluation_docx(
+    submission: &Submission,
+    config: &Config,
+    options: &RenderOptions,
+) -> Result<XMLDocx, RenderError> {
+    let styles = style_sheet(config, options)?;
+    let form = evaluation::evaluation_form(submission, &config.evaluation, styles);
+    let mut doc = metadata::add_custom_properties(document::docx::to_docx(&form), submission);
+    if options.header.unwrap_or(config.template.show_header) {
+        doc = doc.header(page_header(submission));
+    }
+    if options.footer.unwrap_or(config.template.show_footer) {
+        doc = doc.footer(page_footer(Local::now()));
+    }
+
+    let mut xml = doc.build();
+    let mut info = DocumentInfo::from_submission(submission);
+    info.title = form.title;
+    metadata::apply_core_properties(&mut xml, &info);
+    Ok(xml)
+}
+
+// Committee summary of many proposals, with the running footer and the
+// request's branding
+pub fn build_summary_docx(
+    summary: &CommitteeSummary,
+    config: &Config,
+    options: &RenderOptions,
+) -> Result<XMLDocx, RenderError> {
+    let styles = style_sheet(config, options)?;
+    let report = summary::summary_document(summary, styles);
+    let mut doc = document::docx::to_docx(&report);
+    if options.footer.unwrap_or(config.template.show_footer) {
+        doc = doc.footer(page_footer(summary.generated_at));
+    }
+
+    let mut xml = doc.build();
+    let now = Utc::now();
+    metadata::apply_core_properties(
+        &mut xml,
+        &DocumentInfo {
+            title: report.title,
+            author: "COE-DSAI".to_string(),
+            subject: "Seed grant proposals".to_string(),
+            keywords: Vec::new(),
+            created: now,
+            modified: now,
+        },
+    );
+    Ok(xml)
+}
+
+// Sanction letter for an approved proposal, on the letterhead instead of
+// the proposal's running header
+pub fn build_sanction_docx(
+    submission: &Submission,
+    approval: &Approval,
+    config: &Config,
+    options: &RenderOptions,
+) -> Result<XMLDocx, RenderError> {
+    let styles = style_sheet(config, options)?;
+    let letter = sanction::sanction_letter(submission, approval, &config.sanction, styles);
+    let mut xml = metadata::add_custom_properties(document::docx::to_docx(&letter), submission).build();
+    let mut info = DocumentInfo::from_submission(submission);
+    info.title = letter.title;
+    metadata::apply_core_properties(&mut xml, &info);
+    Ok(xml)
+}
+
+// Progress report or utilisation certificate of a funded project, with the
+// proposal's running header and footer
+pub fn build_report_docx(
+    kind: ReportKind,
+    submission: &Submission,
+    expenditure: &Expenditure,
+    config: &Config,
+    options: &RenderOptions,
+) -> Result<XMLDocx, RenderError> {
+    let styles = style_sheet(config, options)?;
+    let report = progress::report(kind, submission, expenditure, styles);
+    let mut doc = metadata::add_custom_properties(document::docx::to_docx(&report), submission);
+    if options.header.unwrap_or(config.template.show_header) {
+        doc = doc.header(page_header(submission));
+    }
+    if options.footer.unwrap_or(config.template.show_footer) {
+        doc = doc.footer(page_footer(Local::now()));
+    }
+
+    let mut xml = doc.build();
+    let mut info = DocumentInfo::from_submission(submission);
+    info.title = report.title;
+    metadata::apply_core_properties(&mut xml, &info);
+    Ok(xml)
+}
+
+// Distinguishes temporary files when the same submission is rendered
+// concurrently (e.g. twice in one batch).
+static RENDER_SEQ: AtomicUsize = AtomicUsize::new(0);
+
+// Renders the proposal to PDF via LibreOffice, signing it if enabled. The
+// PDF is also left in `output/` under its standard name.
+pub fn render_pdf(
+    submission: &Submission,
+    config: &Config,
+    options: &RenderOptions,
+) -> Result<Vec<u8>, RenderError> {
+    let pdfa = options.pdfa.unwrap_or(config.template.pdfa);
+    let sign = signing_enabled(config, options);
+    if sign && config.signing.signer.is_none() {
+        return Err(RenderError::SigningUnavailable);
+    }
+
+    let filename = pdf_filename(submission);
+    let pdf = convert_to_pdf(build_docx(submission, config, options)?, &filename, pdfa)?;
+    if sign {
+        sign_pdf(pdf, &filename, &submission.unique_id, config)
+    } else {
+        Ok(pdf)
+    }
+}
+
+// Signs a PDF converted to `output/<filename>` and replaces that file with
+// the signed copy
+pub fn sign_pdf(pdf: Vec<u8>, filename: &str, unique_id: &str, config: &Config) -> Result<Vec<u8>, RenderError> {
+    let Some(signer) = &config.signing.signer else {
+        return Err(RenderError::SigningUnavailable);
+    };
+    let signed = signing::sign(&pdf, signer, unique_id, Local::now()).map_err(|e| RenderError::Sign(e.to_string()))?;
+    let temp_path = format!("output/signed_{}.pdf", RENDER_SEQ.fetch_add(1, Ordering::Relaxed));
+    if let Err(e) = fs::write(&temp_path, &signed).and_then(|_| publish(&temp_path, filename)) {
+        println!("Error writing signed PDF: {}", e);
+        let _ = fs::remove_file(&temp_path);
+    }
+    Ok(signed)
+}
+
+// Moves a PDF written under a per-render name to `output/<filename>`. The
+// rename replaces the file in one step, so renders of the same submission
+// never read or overwrite each other's output.
+fn publish(path: &str, filename: &str) -> io::Result<()> {
+    fs::rename(path, format!("output/{}", filename))
+}
+
+// Packs a built document into memory, for endpoints that return the DOCX.
+pub fn pack_docx(xml: XMLDocx) -> Result<Vec<u8>, RenderError> {
+    let mut buffer = io::Cursor::new(Vec::new());
+    xml.pack(&mut buffer)
+        .map_err(|e| RenderError::PackDocx(e.to_string()))?;
+    Ok(buffer.into_inner())
+}
+
+// Writes the document to a temporary DOCX and converts it with LibreOffice,
+// returning the PDF bytes and leaving the PDF as `output/<pdf_filename>`.
+// PDF/A output is checked for conformance before it is returned.
+pub fn convert_to_pdf(xml: XMLDocx, pdf_filename: &str, pdfa: bool) -> Result<Vec<u8>, RenderError> {
+    let stem = pdf_filename.trim_end_matches(".pdf");
+    let seq = RENDER_SEQ.fetch_add(1, Ordering::Relaxed);
+
+    let docx_path = format!("docx_file_{}_{}.docx", stem.trim_start_matches("proposal_"), seq);
+
+    // Ensure output directory exists
+    fs::create_dir_all("output").map_err(RenderError::OutputDir)?;
+
+    // create the Word document
+    let file = fs::File::create(&docx_path).map_err(RenderError::CreateDocx)?;
+    if let Err(e) = xml.pack(file) {
+        let _ = fs::remove_file(&docx_path);
+        return Err(RenderError::PackDocx(e.to_string()));
+    }
+
+    println!("Successfully created DOCX file: {}", docx_path);
+
+    // convert to PDF, named after the DOCX so concurrent renders don't share
+    // an output file
+    let converted = utils::convert_docx_to_pdf(&docx_path, "output", None, pdfa);
+
+    // clean up the intermediate DOCX whether or not conversion worked
+    if let Err(e) = fs::remove_file(&docx_path) {
+        println!("Error removing DOCX file: {}", e);
+    }
+
+    let pdf_path = converted.map_err(RenderError::Convert)?;
+    let pdf = fs::read(&pdf_path).map_err(|e| {
+        println!("PDF file path: {}", pdf_path);
+        // List all files in the output directory
+        if let Ok(entries) = fs::read_dir("output") {
+            println!("Files in output directory:");
+            for entry in entries.flatten() {
+                println!("  {}", entry.path().display());
+            }
+        }
+        RenderError::ReadPdf(e)
+    })?;
+    if let Err(e) = publish(&pdf_path, pdf_filename) {
+        println!("Error moving {} to {}: {}", pdf_path, pdf_filename, e);
+        let _ = fs::remove_file(&pdf_path);
+    }
+
+    if pdfa {
+        let problems = pdfa::check(&pdf);
+        if !problems.is_empty() {
+            return Err(RenderError::Conformance(problems.join("; ")));
+        }
+    } else {
+        // viewers without the font show other glyphs, or boxes
+        let unembedded = pdfa::unembedded_fonts(&pdf);
+        if unembedded > 0 {
+            println!("Warning: {} font(s) not embedded in {}", unembedded, pdf_filename);
+        }
+    }
+    Ok(pdf)
+}
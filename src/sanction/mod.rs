@@ -0,0 +1,381 @@
+use crate::document::{format_date, Block, Cell, Document, Paragraph, Section, StyleName, StyleSheet, Table, TextStyle};
+use crate::models::Submission;
+use crate::page1::load_logo;
+use crate::page2::duration_months;
+use chrono::{Days, Months, NaiveDate};
+use serde::Deserialize;
+use std::env;
+use std::fs;
+
+// Sanction letter issued once a proposal is approved: the office's reference
+// number and date, the project (title, PI, Co-PIs, duration) and the amount
+// sanctioned for each year. The decision itself comes with the request as
+// an `Approval`; the wording comes from the letter template in the JSON file
+// SANCTION_TEMPLATE, e.g.
+//
+//   {"subject": "Sanction of seed grant for \"{title}\"", "conditions": ["..."]}
+//
+// Fields left out keep the centre's standard wording. Template text may use
+// the placeholders {unique_id}, {title}, {pi}, {track}, {duration},
+// {amount}, {reference}, {sanction_date}, {start_date} and {end_date}.
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct LetterTemplate {
+    // Lines under the logo naming the issuing office
+    pub letterhead: Vec<String>,
+    pub subject: String,
+    pub salutation: String,
+    // Paragraphs before the project details
+    pub body: Vec<String>,
+    // Numbered terms and conditions of the grant
+    pub conditions: Vec<String>,
+    pub closing: String,
+    pub signatory: String,
+    pub designation: String,
+    // Offices receiving a copy
+    pub copy_to: Vec<String>,
+}
+
+impl Default for LetterTemplate {
+    fn default() -> Self {
+        let lines = |lines: &[&str]| lines.iter().map(|line| line.to_string()).collect();
+        Self {
+            letterhead: lines(&[
+                "Centre of Excellence in Data Science and Artificial Intelligence",
+                "Thapar Institute of Engineering and Technology, Patiala",
+            ]),
+            subject: "Sanction of COE-DSAI seed grant for the project \"{title}\"".to_string(),
+            salutation: "Dear {pi},".to_string(),
+            body: lines(&[
+                "We are pleased to inform you that your proposal {unique_id}, \"{title}\", has been approved for funding \
+                 under the COE-DSAI Seed Grant scheme.",
+                "A total grant of {amount} is sanctioned for a period of {duration}, released year-wise as below.",
+            ]),
+            conditions: lines(&[
+                "The grant shall be used only for the purposes detailed in the approved budget.",
+                "Funds for each subsequent year are released on receipt of a satisfactory progress report and \
+                 utilisation certificate for the previous year.",
+                "Equipment purchased from the grant is the property of the Institute.",
+                "All publications and outputs shall acknowledge the support of COE-DSAI, TIET.",
+                "Any unspent balance at the end of the project shall be returned to the Centre.",
+            ]),
+            closing: "Yours sincerely,".to_string(),
+            signatory: "Head".to_string(),
+            designation: "COE-DSAI, TIET Patiala".to_string(),
+            copy_to: lines(&["Dean, Finance and Accounts", "Dean, Research and Development", "Office copy"]),
+        }
+    }
+}
+
+impl LetterTemplate {
+    // Reads the template file; without one the default wording applies, and
+    // a file that doesn't parse is ignored with a message
+    pub fn from_env() -> Self {
+        let path = env::var("SANCTION_TEMPLATE").unwrap_or_else(|_| "sanction_template.json".to_string());
+        let data = match fs::read(&path) {
+            Ok(data) => data,
+            Err(_) => return Self::default(),
+        };
+        match serde_json::from_slice(&data) {
+            Ok(template) => {
+                println!("Loaded sanction letter template from {}", path);
+                template
+            }
+            Err(e) => {
+                println!("Ignoring sanction letter template {}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+}
+
+// The sanctioning authority's decision
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct Approval {
+    pub reference_number: String,
+    pub sanction_date: NaiveDate,
+    #[serde(default)]
+    pub start_date: Option<NaiveDate>,
+    // Defaults to the start date plus the duration
+    #[serde(default)]
+    pub end_date: Option<NaiveDate>,
+    // Defaults to the duration the applicant proposed
+    #[serde(default)]
+    pub duration_months: Option<i32>,
+    // Amount sanctioned for each year of the project, in rupees
+    pub sanctioned_amounts: Vec<i64>,
+    // The PI's name; the DMS only has their email
+    #[serde(default)]
+    pub pi_name: Option<String>,
+    // Conditions specific to this grant, after the template's
+    #[serde(default)]
+    pub conditions: Vec<String>,
+}
+
+impl Approval {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.reference_number.trim().is_empty() {
+            return Err("referenceNumber is required".to_string());
+        }
+        if self.sanctioned_amounts.is_empty() {
+            return Err("sanctionedAmounts needs the amount for at least one year".to_string());
+        }
+        if self.sanctioned_amounts.iter().any(|amount| *amount < 0) {
+            return Err("sanctionedAmounts can't be negative".to_string());
+        }
+        if self.duration_months.is_some_and(|months| months <= 0) {
+            return Err("durationMonths must be above 0".to_string());
+        }
+        if let (Some(start), Some(end)) = (self.start_date, self.end_date) {
+            if end < start {
+                return Err("endDate is before startDate".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    pub fn total(&self) -> i64 {
+        self.sanctioned_amounts.iter().sum()
+    }
+}
+
+// Body of POST /submissions/sanction-letter
+#[derive(Deserialize)]
+pub struct SanctionRequest {
+    pub submission: Submission,
+    pub approval: Approval,
+}
+
+pub fn letter_filename(submission: &Submission, extension: &str) -> String {
+    format!("sanction_letter_{}.{}", submission.unique_id, extension)
+}
+
+// Rupees with Indian digit grouping, e.g. "Rs. 12,40,000"
+pub fn rupees(amount: i64) -> String {
+    let digits = amount.unsigned_abs().to_string();
+    let grouped = if digits.len() <= 3 {
+        digits
+    } else {
+        let (rest, last) = digits.split_at(digits.len() - 3);
+        let mut groups: Vec<&str> = Vec::new();
+        let mut end = rest.len();
+        while end > 0 {
+            let start = end.saturating_sub(2);
+            groups.insert(0, &rest[start..end]);
+            end = start;
+        }
+        format!("{},{}", groups.join(","), last)
+    };
+    let sign = if amount < 0 { "-" } else { "" };
+    format!("Rs. {}{}", sign, grouped)
+}
+
+// Whole years as years, otherwise months: "2 years", "18 months"
+fn format_duration(months: i32) -> String {
+    match months {
+        12 => "1 year".to_string(),
+        m if m > 0 && m % 12 == 0 => format!("{} years", m / 12),
+        1 => "1 month".to_string(),
+        m => format!("{} months", m),
+    }
+}
+
+// Values of the template's placeholders for one letter
+struct Letter<'a> {
+    submission: &'a Submission,
+    approval: &'a Approval,
+    months: i32,
+    end_date: Option<NaiveDate>,
+}
+
+impl<'a> Letter<'a> {
+    fn new(submission: &'a Submission, approval: &'a Approval) -> Self {
+        // without a usable proposed duration, one year per sanctioned amount
+        let months = approval
+            .duration_months
+            .or(Some(duration_months(submission)).filter(|months| *months > 0))
+            .unwrap_or(approval.sanctioned_amounts.len() as i32 * 12);
+        // the day before the same date `months` later
+        let end_date = approval.end_date.or_else(|| {
+            let start = approval.start_date?;
+            start
+                .checked_add_months(Months::new(months.max(0) as u32))?
+                .checked_sub_days(Days::new(1))
+        });
+        Self {
+            submission,
+            approval,
+            months,
+            end_date,
+        }
+    }
+
+    fn title(&self) -> String {
+        self.submission
+            .project_title
+            .clone()
+            .unwrap_or_else(|| self.submission.unique_id.clone())
+    }
+
+    fn pi(&self) -> String {
+        self.approval
+            .pi_name
+            .clone()
+            .unwrap_or_else(|| self.submission.user.clone())
+    }
+
+    fn fill(&self, text: &str) -> String {
+        let date = |date: Option<NaiveDate>| date.map_or_else(String::new, format_date);
+        [
+            ("{unique_id}", self.submission.unique_id.clone()),
+            ("{title}", self.title()),
+            ("{pi}", self.pi()),
+            ("{track}", self.submission.track.clone()),
+            ("{duration}", format_duration(self.months)),
+            ("{amount}", rupees(self.approval.total())),
+            ("{reference}", self.approval.reference_number.clone()),
+            ("{sanction_date}", format_date(self.approval.sanction_date)),
+            ("{start_date}", date(self.approval.start_date)),
+            ("{end_date}", date(self.end_date)),
+        ]
+        .iter()
+        .fold(text.to_string(), |text, (placeholder, value)| text.replace(placeholder, value))
+    }
+}
+
+fn project_table(letter: &Letter) -> Table {
+    let co_pis = letter
+        .submission
+        .co_pi
+        .iter()
+        .flatten()
+        .map(|co_pi| format!("{} ({})", co_pi.name, co_pi.email))
+        .collect::<Vec<_>>()
+        .join("; ");
+    let period = match (letter.approval.start_date, letter.end_date) {
+        (Some(start), Some(end)) => format!("{} ({} to {})", format_duration(letter.months), format_date(start), format_date(end)),
+        _ => format_duration(letter.months),
+    };
+
+    let mut rows = vec![
+        vec![Cell::bold("Unique ID").width(2500), Cell::new(&letter.submission.unique_id).width(6000)],
+        vec![Cell::bold("Project Title"), Cell::new(letter.title())],
+        vec![Cell::bold("Principal Investigator"), Cell::new(letter.pi())],
+    ];
+    if !co_pis.is_empty() {
+        rows.push(vec![Cell::bold("Co-Principal Investigator(s)"), Cell::new(co_pis)]);
+    }
+    rows.push(vec![Cell::bold("Track"), Cell::new(&letter.submission.track)]);
+    rows.push(vec![Cell::bold("Duration"), Cell::new(period)]);
+    Table { rows }
+}
+
+fn amounts_table(approval: &Approval) -> Table {
+    let mut rows = vec![vec![Cell::bold("Year").width(4000), Cell::bold("Amount Sanctioned").width(4500)]];
+    for (index, amount) in approval.sanctioned_amounts.iter().enumerate() {
+        rows.push(vec![Cell::new(format!("Year {}", index + 1)), Cell::new(rupees(*amount))]);
+    }
+    rows.push(vec![Cell::bold("Total"), Cell::bold(&rupees(approval.total()))]);
+    Table { rows }
+}
+
+pub fn sanction_letter(
+    submission: &Submission,
+    approval: &Approval,
+    template: &LetterTemplate,
+    styles: &StyleSheet,
+) -> Document {
+    let letter = Letter::new(submission, approval);
+
+    let mut blocks = Vec::new();
+    if let Some(logo) = load_logo() {
+        blocks.push(Block::Image(logo));
+    }
+    for line in &template.letterhead {
+        blocks.push(Block::styled(&letter.fill(line), StyleName::Institution));
+    }
+    blocks.push(Block::Blank);
+    blocks.push(Block::field("Ref. No.: ", &approval.reference_number));
+    blocks.push(Block::field("Date: ", &format_date(approval.sanction_date)));
+    blocks.push(Block::Blank);
+    blocks.push(Block::Text("To,".to_string()));
+    blocks.push(Block::Text(letter.pi()));
+    if letter.pi() != submission.user {
+        blocks.push(Block::Text(submission.user.clone()));
+    }
+    blocks.push(Block::Blank);
+    blocks.push(Block::field("Subject: ", &letter.fill(&template.subject)));
+    blocks.push(Block::Blank);
+    blocks.push(Block::Text(letter.fill(&template.salutation)));
+    for paragraph in &template.body {
+        blocks.push(Block::Text(letter.fill(paragraph)));
+    }
+    blocks.push(Block::Spacer);
+    blocks.push(Block::Heading("Project Details".to_string()));
+    blocks.push(Block::Table(project_table(&letter)));
+    blocks.push(Block::Spacer);
+    blocks.push(Block::Heading("Sanctioned Amount".to_string()));
+    blocks.push(Block::Table(amounts_table(approval)));
+
+    let conditions: Vec<&String> = template.conditions.iter().chain(&approval.conditions).collect();
+    if !conditions.is_empty() {
+        blocks.push(Block::Spacer);
+        blocks.push(Block::Heading("Terms and Conditions".to_string()));
+        for (index, condition) in conditions.into_iter().enumerate() {
+            blocks.push(Block::Paragraph(
+                Paragraph::styled(StyleName::ListItem).text(format!("{}. {}", index + 1, letter.fill(condition)), TextStyle::new()),
+            ));
+        }
+    }
+
+    blocks.push(Block::Spacer);
+    blocks.push(Block::Text(letter.fill(&template.closing)));
+    blocks.extend(vec![Block::Blank; 3]);
+    blocks.push(Block::styled(&letter.fill(&template.signatory), StyleName::Signature));
+    blocks.push(Block::styled(&letter.fill(&template.designation), StyleName::Signature));
+
+    if !template.copy_to.is_empty() {
+        blocks.push(Block::Blank);
+        blocks.push(Block::field("Copy to:", ""));
+        blocks.push(Block::List(template.copy_to.iter().map(|line| letter.fill(line)).collect()));
+    }
+
+    Document {
+        title: format!("Sanction Letter: {}", submission.unique_id),
+        styles: styles.clone(),
+        sections: vec![Section::new(blocks)],
+        landscape: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approval() -> Approval {
+        Approval {
+            reference_number: "COE-DSAI/SG/2026/014".to_string(),
+            sanction_date: NaiveDate::from_ymd_opt(2026, 10, 15).unwrap(),
+            start_date: NaiveDate::from_ymd_opt(2026, 11, 1),
+            end_date: None,
+            duration_months: None,
+            sanctioned_amounts: vec![780000, 250000],
+            pi_name: None,
+            conditions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn falls_back_to_a_year_per_sanctioned_amount() {
+        let mut submission: Submission = serde_json::from_str(include_str!("../../mock_submission.json")).unwrap();
+        submission.project_duration = None;
+        let approval = approval();
+
+        let letter = Letter::new(&submission, &approval);
+        assert_eq!(letter.months, 24);
+        assert_eq!(letter.end_date, NaiveDate::from_ymd_opt(2028, 10, 31));
+        assert_eq!(letter.fill("{duration}"), "2 years");
+    }
+}
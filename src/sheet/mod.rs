@@ -0,0 +1,141 @@
+use rust_xlsxwriter::{Format, Formula, Workbook, XlsxError};
+
+// Spreadsheet exports. Reports offered as spreadsheets build their tables
+// as Sheets, written either as CSV (tables one after another, separated by
+// a blank line) or as an XLSX workbook with one worksheet per table.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Text(String),
+    Number(i64),
+    // Spreadsheet formula such as "=SUM(C2:C5)" and its value, which is
+    // what CSV gets
+    Formula { formula: String, result: i64 },
+}
+
+// Spreadsheet name of a 0-based column: A, B, ..., Z, AA, ...
+pub fn column_name(col: usize) -> String {
+    let mut name = String::new();
+    let mut n = col + 1;
+    while n > 0 {
+        n -= 1;
+        name.insert(0, (b'A' + (n % 26) as u8) as char);
+        n /= 26;
+    }
+    name
+}
+
+// Spreadsheet reference of a cell, counting the header as row 0
+pub fn cell_ref(row: usize, col: usize) -> String {
+    format!("{}{}", column_name(col), row + 1)
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::Text(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::Text(value)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::Number(value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sheet {
+    // Worksheet name; at most 31 characters, no []:*?/\
+    pub name: String,
+    pub header: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+impl Sheet {
+    pub fn new(name: &str, header: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            header: header.iter().map(|title| title.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+}
+
+fn csv_field(value: &Value) -> String {
+    let text = match value {
+        Value::Number(n) | Value::Formula { result: n, .. } => return n.to_string(),
+        Value::Text(text) => text,
+    };
+    // Excel runs text starting with these as a formula
+    let text = if text.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", text)
+    } else {
+        text.clone()
+    };
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+fn csv_row(out: &mut String, values: &[Value]) {
+    let fields: Vec<String> = values.iter().map(csv_field).collect();
+    out.push_str(&fields.join(","));
+    out.push_str("\r\n");
+}
+
+// CSV with a byte order mark, without which Excel misreads UTF-8 (names
+// and titles in Hindi or Punjabi)
+pub fn to_csv(sheets: &[Sheet]) -> String {
+    let mut out = String::from("\u{feff}");
+    for (i, sheet) in sheets.iter().enumerate() {
+        if i > 0 {
+            out.push_str("\r\n");
+        }
+        let header: Vec<Value> = sheet.header.iter().map(|title| Value::from(title.as_str())).collect();
+        csv_row(&mut out, &header);
+        for row in &sheet.rows {
+            csv_row(&mut out, row);
+        }
+    }
+    out
+}
+
+pub fn to_xlsx(sheets: &[Sheet]) -> Result<Vec<u8>, XlsxError> {
+    let mut workbook = Workbook::new();
+    let bold = Format::new().set_bold();
+    let number = Format::new().set_num_format("#,##0");
+
+    for sheet in sheets {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name(&sheet.name)?;
+        for (col, title) in sheet.header.iter().enumerate() {
+            worksheet.write_string_with_format(0, col as u16, title, &bold)?;
+        }
+        for (row, values) in sheet.rows.iter().enumerate() {
+            let row = row as u32 + 1;
+            for (col, value) in values.iter().enumerate() {
+                match value {
+                    Value::Text(text) => worksheet.write_string(row, col as u16, text)?,
+                    Value::Number(n) => worksheet.write_number_with_format(row, col as u16, *n as f64, &number)?,
+                    Value::Formula { formula, result } => worksheet.write_formula_with_format(
+                        row,
+                        col as u16,
+                        Formula::new(formula).set_result(result.to_string()),
+                        &number,
+                    )?,
+                };
+            }
+        }
+        // keep the header in view while scrolling
+        worksheet.set_freeze_panes(1, 0)?;
+        worksheet.autofit();
+    }
+    workbook.save_to_buffer()
+}
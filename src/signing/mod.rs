@@ -0,0 +1,578 @@
+use crate::pdfa::find;
+use chrono::{DateTime, Local};
+use openssl::cms::{CMSOptions, CmsContentInfo};
+use openssl::nid::Nid;
+use openssl::pkcs12::Pkcs12;
+use openssl::pkey::{PKey, Private};
+use openssl::stack::Stack;
+use openssl::x509::X509;
+use serde::Serialize;
+use std::fmt;
+use std::fs;
+
+// PAdES signatures for rendered PDFs. The signature is appended as an
+// incremental update: a signature field on page 1 (invisible), the catalog's
+// AcroForm, and a new document information dictionary carrying the
+// submission's unique_id, so the ID is covered by the signature too. The
+// CMS signature is detached (ETSI.CAdES.detached) and carries the
+// signing-certificate-v2 attribute PAdES requires.
+//
+// Like the PDF/A check, this works on LibreOffice's output directly:
+// uncompressed object dictionaries and a classic cross-reference table.
+
+// CMS_CADES (OpenSSL 3.0+), which the openssl crate doesn't name: adds the
+// signing-certificate-v2 attribute
+const CMS_CADES: u32 = 0x100000;
+
+// Hex digits reserved for the CMS signature beyond the certificates it
+// carries
+const SIGNATURE_RESERVE: usize = 8192;
+
+#[derive(Debug, Clone)]
+pub struct Signer {
+    key: PKey<Private>,
+    cert: X509,
+    chain: Vec<X509>,
+    // Common name of the certificate, shown as the signer
+    pub name: String,
+    pub reason: String,
+}
+
+#[derive(Debug)]
+pub enum SigningError {
+    // The PDF isn't structured the way this module can update
+    Unsupported(String),
+    Sign(openssl::error::ErrorStack),
+}
+
+impl fmt::Display for SigningError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SigningError::Unsupported(e) => write!(f, "cannot sign this PDF: {}", e),
+            SigningError::Sign(e) => write!(f, "signing failed: {}", e),
+        }
+    }
+}
+
+impl Signer {
+    // Reads the key and certificate (plus any CA certificates) from a
+    // PKCS#12 file
+    pub fn load(path: &str, password: &str, reason: &str) -> Result<Self, String> {
+        let der = fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+        let parsed = Pkcs12::from_der(&der)
+            .and_then(|p12| p12.parse2(password))
+            .map_err(|e| format!("{}: {}", path, e))?;
+        let (Some(key), Some(cert)) = (parsed.pkey, parsed.cert) else {
+            return Err(format!("{}: no private key and certificate", path));
+        };
+        let name = cert
+            .subject_name()
+            .entries_by_nid(Nid::COMMONNAME)
+            .next()
+            .and_then(|entry| entry.data().as_utf8().ok())
+            .map(|name| name.to_string())
+            .unwrap_or_default();
+
+        Ok(Self {
+            key,
+            cert,
+            chain: parsed.ca.map(|ca| ca.into_iter().collect()).unwrap_or_default(),
+            name,
+            reason: reason.to_string(),
+        })
+    }
+
+    // SHA-256 of the certificate, identifying which key signed a cached PDF
+    pub fn fingerprint(&self) -> Vec<u8> {
+        self.cert
+            .digest(openssl::hash::MessageDigest::sha256())
+            .map(|digest| digest.to_vec())
+            .unwrap_or_default()
+    }
+
+    fn certificates(&self) -> Result<Stack<X509>, openssl::error::ErrorStack> {
+        let mut stack = Stack::new()?;
+        for cert in &self.chain {
+            stack.push(cert.clone())?;
+        }
+        Ok(stack)
+    }
+}
+
+fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).rposition(|window| window == needle)
+}
+
+fn is_delimiter(byte: u8) -> bool {
+    byte.is_ascii_whitespace() || b"()<>[]{}/%".contains(&byte)
+}
+
+// Leading integer of `bytes`, skipping whitespace
+fn parse_int(bytes: &[u8]) -> Option<(usize, usize)> {
+    let start = bytes.iter().position(|b| !b.is_ascii_whitespace())?;
+    let len = bytes[start..].iter().take_while(|b| b.is_ascii_digit()).count();
+    let value = std::str::from_utf8(&bytes[start..start + len]).ok()?.parse().ok()?;
+    Some((value, start + len))
+}
+
+// Object number of the reference after `key` in `dict` ("/Root 12 0 R")
+fn reference(dict: &[u8], key: &str) -> Option<usize> {
+    let key = key.as_bytes();
+    let mut from = 0;
+    while let Some(position) = find(dict, key, from) {
+        from = position + 1;
+        let rest = &dict[position + key.len()..];
+        // "/Page" must not match "/Pages"
+        if rest.first().is_some_and(|b| !is_delimiter(*b)) {
+            continue;
+        }
+        let (number, used) = parse_int(rest)?;
+        let (_, generation) = parse_int(&rest[used..])?;
+        return rest[used + generation..]
+            .iter()
+            .find(|b| !b.is_ascii_whitespace())
+            .filter(|b| **b == b'R')
+            .map(|_| number);
+    }
+    None
+}
+
+// Dictionary of object `number`, from its last definition (later
+// incremental updates replace earlier ones)
+fn object(pdf: &[u8], number: usize) -> Option<&[u8]> {
+    let header = format!("{} 0 obj", number);
+    let mut start = None;
+    let mut from = 0;
+    while let Some(position) = find(pdf, header.as_bytes(), from) {
+        from = position + 1;
+        if position == 0 || is_delimiter(pdf[position - 1]) {
+            start = Some(position + header.len());
+        }
+    }
+    let start = start?;
+    let end = find(pdf, b"endobj", start)?;
+    let body = &pdf[start..end];
+    let open = find(body, b"<<", 0)?;
+    let close = rfind(body, b">>")?;
+    Some(&body[open..close + 2])
+}
+
+// `dict` with `entries` added before its closing ">>"
+fn extend_dict(dict: &[u8], entries: &str) -> Vec<u8> {
+    let mut extended = dict[..dict.len() - 2].to_vec();
+    extended.extend_from_slice(entries.as_bytes());
+    extended.extend_from_slice(b">>");
+    extended
+}
+
+fn first_page(pdf: &[u8], pages: usize, depth: usize) -> Option<usize> {
+    let dict = object(pdf, pages)?;
+    let kids = find(dict, b"/Kids", 0)?;
+    let open = find(dict, b"[", kids)?;
+    let (kid, _) = parse_int(&dict[open + 1..])?;
+    let kid_dict = object(pdf, kid)?;
+    if find(kid_dict, b"/Kids", 0).is_some() && depth < 8 {
+        first_page(pdf, kid, depth + 1)
+    } else {
+        Some(kid)
+    }
+}
+
+// PDF text string: literal for ASCII, UTF-16BE hex otherwise
+fn text_string(text: &str) -> String {
+    if text.is_ascii() {
+        let escaped = text.replace('\\', "\\\\").replace('(', "\\(").replace(')', "\\)");
+        format!("({})", escaped)
+    } else {
+        let hex: String = text.encode_utf16().map(|unit| format!("{:04X}", unit)).collect();
+        format!("<FEFF{}>", hex)
+    }
+}
+
+// Decodes the text string at the start of `bytes`
+fn parse_text_string(bytes: &[u8]) -> Option<String> {
+    let start = bytes.iter().position(|b| !b.is_ascii_whitespace())?;
+    let bytes = &bytes[start..];
+    match bytes.first()? {
+        b'(' => {
+            let mut text = Vec::new();
+            let mut escaped = false;
+            for &byte in &bytes[1..] {
+                match (escaped, byte) {
+                    (true, _) => {
+                        text.push(byte);
+                        escaped = false;
+                    }
+                    (false, b'\\') => escaped = true,
+                    (false, b')') => return String::from_utf8(text).ok(),
+                    (false, _) => text.push(byte),
+                }
+            }
+            None
+        }
+        b'<' => {
+            let end = bytes.iter().position(|b| *b == b'>')?;
+            let hex = std::str::from_utf8(&bytes[1..end]).ok()?;
+            let units: Vec<u16> = hex
+                .trim_start_matches("FEFF")
+                .as_bytes()
+                .chunks(4)
+                .filter_map(|chunk| u16::from_str_radix(std::str::from_utf8(chunk).ok()?, 16).ok())
+                .collect();
+            String::from_utf16(&units).ok()
+        }
+        _ => None,
+    }
+}
+
+fn pdf_date(at: &DateTime<Local>) -> String {
+    // D:YYYYMMDDHHmmSS+05'30'
+    format!("{}{}'", at.format("D:%Y%m%d%H%M%S"), at.format("%:z").to_string().replace(':', "'"))
+}
+
+fn unsupported(message: &str) -> SigningError {
+    SigningError::Unsupported(message.to_string())
+}
+
+// Signs `pdf`, recording `unique_id` in the document information
+pub fn sign(pdf: &[u8], signer: &Signer, unique_id: &str, at: DateTime<Local>) -> Result<Vec<u8>, SigningError> {
+    let startxref = rfind(pdf, b"startxref").ok_or_else(|| unsupported("no startxref"))?;
+    let (prev_xref, _) =
+        parse_int(&pdf[startxref + b"startxref".len()..]).ok_or_else(|| unsupported("no startxref"))?;
+    let trailer_start = rfind(&pdf[..startxref], b"trailer")
+        .ok_or_else(|| unsupported("cross-reference streams are not supported"))?;
+    let trailer = &pdf[trailer_start..startxref];
+
+    let size_key = find(trailer, b"/Size", 0).ok_or_else(|| unsupported("trailer has no /Size"))?;
+    let (size, _) = parse_int(&trailer[size_key + b"/Size".len()..]).ok_or_else(|| unsupported("trailer has no /Size"))?;
+    let root = reference(trailer, "/Root").ok_or_else(|| unsupported("trailer has no /Root"))?;
+    let id = find(trailer, b"/ID", 0).and_then(|start| {
+        let end = find(trailer, b"]", start)?;
+        Some(String::from_utf8_lossy(&trailer[start..=end]).into_owned())
+    });
+
+    let catalog = object(pdf, root).ok_or_else(|| unsupported("catalog not found"))?;
+    if find(catalog, b"/AcroForm", 0).is_some() {
+        return Err(unsupported("document already has a form or signature"));
+    }
+    let pages = reference(catalog, "/Pages").ok_or_else(|| unsupported("catalog has no /Pages"))?;
+    let page = first_page(pdf, pages, 0).ok_or_else(|| unsupported("no pages"))?;
+    let page_dict = object(pdf, page).ok_or_else(|| unsupported("page not found"))?;
+
+    let (signature, field, appearance, info) = (size, size + 1, size + 2, size + 3);
+
+    let page_dict = match find(page_dict, b"/Annots", 0) {
+        None => extend_dict(page_dict, &format!("/Annots[{} 0 R]", field)),
+        Some(annots) => {
+            let open = page_dict[annots + b"/Annots".len()..]
+                .iter()
+                .position(|b| !b.is_ascii_whitespace())
+                .map(|offset| annots + b"/Annots".len() + offset)
+                .filter(|&position| page_dict[position] == b'[')
+                .ok_or_else(|| unsupported("page annotations are an indirect array"))?;
+            let mut updated = page_dict[..=open].to_vec();
+            updated.extend_from_slice(format!("{} 0 R ", field).as_bytes());
+            updated.extend_from_slice(&page_dict[open + 1..]);
+            updated
+        }
+    };
+    let catalog = extend_dict(catalog, &format!("/AcroForm<</Fields[{} 0 R]/SigFlags 3>>", field));
+    let info_entries = format!("/UniqueID{}", text_string(unique_id));
+    let info_dict = match reference(trailer, "/Info").and_then(|number| object(pdf, number)) {
+        Some(dict) => extend_dict(dict, &info_entries),
+        None => format!("<<{}>>", info_entries).into_bytes(),
+    };
+
+    let certificates = signer.certificates().map_err(SigningError::Sign)?;
+    let reserved = 2 * (signer.cert.to_der().map_err(SigningError::Sign)?.len()
+        + signer.chain.iter().filter_map(|cert| cert.to_der().ok()).map(|der| der.len()).sum::<usize>())
+        + SIGNATURE_RESERVE;
+
+    let mut out = pdf.to_vec();
+    if !out.ends_with(b"\n") {
+        out.push(b'\n');
+    }
+    let mut offsets = Vec::new();
+    let mut write_object = |out: &mut Vec<u8>, number: usize, body: &[u8]| {
+        offsets.push((number, out.len()));
+        out.extend_from_slice(format!("{} 0 obj\n", number).as_bytes());
+        out.extend_from_slice(body);
+        out.extend_from_slice(b"\nendobj\n");
+    };
+
+    write_object(&mut out, root, &catalog);
+    write_object(&mut out, page, &page_dict);
+
+    // ByteRange is patched in place once the offsets are known, so its
+    // numbers are padded to a fixed width
+    let byte_range_placeholder = format!("[0 {:<10} {:<10} {:<10}]", 0, 0, 0);
+    let signature_dict = format!(
+        "<</Type/Sig/Filter/Adobe.PPKLite/SubFilter/ETSI.CAdES.detached/ByteRange {}/Contents <{}>/M{}/Name{}/Reason{}>>",
+        byte_range_placeholder,
+        "0".repeat(reserved),
+        text_string(&pdf_date(&at)),
+        text_string(&signer.name),
+        text_string(&signer.reason),
+    );
+    let signature_offset = out.len();
+    write_object(&mut out, signature, signature_dict.as_bytes());
+    write_object(
+        &mut out,
+        field,
+        format!(
+            "<</Type/Annot/Subtype/Widget/FT/Sig/T(Signature1)/V {} 0 R/F 132/Rect[0 0 0 0]/P {} 0 R/AP<</N {} 0 R>>>>",
+            signature, page, appearance
+        )
+        .as_bytes(),
+    );
+    write_object(
+        &mut out,
+        appearance,
+        b"<</Type/XObject/Subtype/Form/BBox[0 0 0 0]/Length 0>>\nstream\n\nendstream",
+    );
+    write_object(&mut out, info, &info_dict);
+
+    let xref = out.len();
+    out.extend_from_slice(b"xref\n");
+    for (number, offset) in &offsets {
+        out.extend_from_slice(format!("{} 1\n{:010} 00000 n \n", number, offset).as_bytes());
+    }
+    out.extend_from_slice(
+        format!(
+            "trailer\n<</Size {}/Root {} 0 R/Info {} 0 R/Prev {}{}>>\nstartxref\n{}\n%%EOF\n",
+            info + 1,
+            root,
+            info,
+            prev_xref,
+            id.unwrap_or_default(),
+            xref
+        )
+        .as_bytes(),
+    );
+
+    // the signed bytes are everything except the <...> of /Contents
+    let contents_start = find(&out, b"/Contents <", signature_offset).ok_or_else(|| unsupported("no /Contents"))?
+        + b"/Contents ".len();
+    let contents_end = contents_start + reserved + 2;
+    let byte_range = format!(
+        "[0 {:<10} {:<10} {:<10}]",
+        contents_start,
+        contents_end,
+        out.len() - contents_end
+    );
+    let byte_range_start = find(&out, byte_range_placeholder.as_bytes(), signature_offset)
+        .ok_or_else(|| unsupported("no /ByteRange"))?;
+    out[byte_range_start..byte_range_start + byte_range.len()].copy_from_slice(byte_range.as_bytes());
+
+    let mut signed = out[..contents_start].to_vec();
+    signed.extend_from_slice(&out[contents_end..]);
+    let flags = CMSOptions::DETACHED | CMSOptions::BINARY | CMSOptions::NOSMIMECAP | CMSOptions::from_bits_retain(CMS_CADES);
+    let cms = CmsContentInfo::sign(Some(&signer.cert), Some(&signer.key), Some(&certificates), Some(&signed), flags)
+        .and_then(|cms| cms.to_der())
+        .map_err(SigningError::Sign)?;
+
+    let hex: String = cms.iter().map(|byte| format!("{:02X}", byte)).collect();
+    if hex.len() > reserved {
+        return Err(unsupported("signature larger than the space reserved for it"));
+    }
+    out[contents_start + 1..contents_start + 1 + hex.len()].copy_from_slice(hex.as_bytes());
+    Ok(out)
+}
+
+// Result of checking a PDF's signature against this service's certificate
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Verification {
+    pub signed: bool,
+    // Signed with this service's key and unchanged since
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unique_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signer: Option<String>,
+    // PDF date of the signature, e.g. D:20250401103000+05'30'
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signed_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Verification {
+    fn invalid(mut self, error: &str) -> Self {
+        self.error = Some(error.to_string());
+        self
+    }
+}
+
+// Checks the last signature in `pdf`
+pub fn verify(pdf: &[u8], signer: &Signer) -> Verification {
+    let mut result = Verification::default();
+
+    let Some(byte_range) = rfind(pdf, b"/ByteRange") else {
+        return result.invalid("document is not signed");
+    };
+    result.signed = true;
+
+    let mut numbers = Vec::new();
+    let mut rest = &pdf[byte_range + b"/ByteRange".len()..];
+    if let Some(open) = rest.iter().position(|b| *b == b'[') {
+        rest = &rest[open + 1..];
+        while let Some((number, used)) = parse_int(rest) {
+            numbers.push(number);
+            rest = &rest[used..];
+        }
+    }
+    let [start, first_len, second_start, second_len] = numbers[..] else {
+        return result.invalid("malformed /ByteRange");
+    };
+    if start != 0 || first_len >= second_start || second_start.checked_add(second_len) != Some(pdf.len()) {
+        return result.invalid("document was changed after signing");
+    }
+    let contents = &pdf[first_len..second_start];
+    if contents.first() != Some(&b'<') || contents.last() != Some(&b'>') {
+        return result.invalid("malformed signature contents");
+    }
+    let der: Vec<u8> = contents[1..contents.len() - 1]
+        .chunks(2)
+        .filter_map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect();
+
+    let signature_dict = rfind(&pdf[..byte_range], b"obj")
+        .and_then(|start| Some(&pdf[start..find(pdf, b"endobj", byte_range)?]))
+        .unwrap_or_default();
+    result.signed_at = find(signature_dict, b"/M", 0)
+        .filter(|&position| signature_dict.get(position + 2).is_some_and(|b| is_delimiter(*b)))
+        .and_then(|position| parse_text_string(&signature_dict[position + 2..]));
+
+    let mut signed = pdf[..first_len].to_vec();
+    signed.extend_from_slice(&pdf[second_start..]);
+    result.unique_id =
+        rfind(&signed, b"/UniqueID").and_then(|position| parse_text_string(&signed[position + b"/UniqueID".len()..]));
+
+    let verified = (|| {
+        let mut cms = CmsContentInfo::from_der(&der)?;
+        let mut certs = Stack::new()?;
+        certs.push(signer.cert.clone())?;
+        // only this service's certificate counts as a signer; it is trusted
+        // as configured, so its chain isn't validated
+        let flags = CMSOptions::BINARY | CMSOptions::NOINTERN | CMSOptions::NO_SIGNER_CERT_VERIFY;
+        cms.verify(Some(&certs), None, Some(&signed), None, flags)
+    })();
+    match verified {
+        Ok(()) => {
+            result.valid = true;
+            result.signer = Some(signer.name.clone());
+            result
+        }
+        Err(e) => {
+            println!("Signature verification failed: {}", e);
+            result.invalid("signature does not match this service's certificate or the signed content")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::asn1::Asn1Time;
+    use openssl::bn::BigNum;
+    use openssl::hash::MessageDigest;
+    use openssl::rsa::Rsa;
+    use openssl::x509::X509NameBuilder;
+
+    const PASSWORD: &str = "test";
+
+    // A signer with a fresh self-signed certificate, loaded from a PKCS#12
+    // file like the configured one
+    fn signer(name: &str) -> Signer {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let mut subject = X509NameBuilder::new().unwrap();
+        subject.append_entry_by_nid(Nid::COMMONNAME, name).unwrap();
+        let subject = subject.build();
+
+        let mut cert = X509::builder().unwrap();
+        cert.set_version(2).unwrap();
+        cert.set_serial_number(&BigNum::from_u32(1).unwrap().to_asn1_integer().unwrap())
+            .unwrap();
+        cert.set_subject_name(&subject).unwrap();
+        cert.set_issuer_name(&subject).unwrap();
+        cert.set_pubkey(&key).unwrap();
+        cert.set_not_before(&Asn1Time::days_from_now(0).unwrap()).unwrap();
+        cert.set_not_after(&Asn1Time::days_from_now(1).unwrap()).unwrap();
+        cert.sign(&key, MessageDigest::sha256()).unwrap();
+        let cert = cert.build();
+
+        let p12 = Pkcs12::builder().name(name).pkey(&key).cert(&cert).build2(PASSWORD).unwrap();
+        // tests run in parallel, so each signer gets its own file
+        static NEXT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        let n = NEXT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!("dms-pdfmaker-signer-{}-{}.p12", std::process::id(), n));
+        fs::write(&path, p12.to_der().unwrap()).unwrap();
+        let signer = Signer::load(path.to_str().unwrap(), PASSWORD, "Test signature");
+        let _ = fs::remove_file(&path);
+        signer.unwrap()
+    }
+
+    fn signed_proposal(signer: &Signer) -> Vec<u8> {
+        let pdf = fs::read("proposal.pdf").unwrap();
+        sign(&pdf, signer, "DSAI2025-EE-1007", Local::now()).unwrap()
+    }
+
+    #[test]
+    fn verifies_its_own_signature() {
+        let signer = signer("Test Signer");
+        let signed = signed_proposal(&signer);
+
+        let verification = verify(&signed, &signer);
+        assert!(verification.signed);
+        assert!(verification.valid, "{:?}", verification.error);
+        assert_eq!(verification.unique_id.as_deref(), Some("DSAI2025-EE-1007"));
+        assert_eq!(verification.signer.as_deref(), Some("Test Signer"));
+        assert!(verification.signed_at.is_some_and(|at| at.starts_with("D:")));
+    }
+
+    #[test]
+    fn detects_tampering() {
+        let signer = signer("Test Signer");
+        let signed = signed_proposal(&signer);
+
+        // a changed byte inside the signed range
+        let mut changed = signed.clone();
+        let position = find(&changed, b"/Type", 0).unwrap() + 1;
+        changed[position] = b'X';
+        let verification = verify(&changed, &signer);
+        assert!(verification.signed);
+        assert!(!verification.valid);
+
+        // content appended after signing falls outside the byte range
+        let mut appended = signed;
+        appended.extend_from_slice(b"\n% appended\n");
+        let verification = verify(&appended, &signer);
+        assert!(!verification.valid);
+        assert_eq!(verification.error.as_deref(), Some("document was changed after signing"));
+    }
+
+    #[test]
+    fn rejects_other_certificates_and_unsigned_pdfs() {
+        let signed = signed_proposal(&signer("Test Signer"));
+
+        let verification = verify(&signed, &signer("Someone Else"));
+        assert!(verification.signed);
+        assert!(!verification.valid);
+
+        let unsigned = verify(&fs::read("proposal.pdf").unwrap(), &signer("Test Signer"));
+        assert!(!unsigned.signed);
+        assert!(!unsigned.valid);
+    }
+
+    #[test]
+    fn refuses_to_sign_twice() {
+        let signer = signer("Test Signer");
+        let signed = signed_proposal(&signer);
+
+        assert!(matches!(
+            sign(&signed, &signer, "DSAI2025-EE-1007", Local::now()),
+            Err(SigningError::Unsupported(_))
+        ));
+    }
+}
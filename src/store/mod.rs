@@ -0,0 +1,167 @@
+use crate::models::{Submission, SubmissionStatus};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+// Embedded store of every submission payload the service has rendered,
+// kept as a version history per unique_id so any earlier PDF can be
+// reproduced from exactly the data it was generated from. Each version
+// holds the JSON as received next to the parsed submission that was
+// rendered. A payload identical to the latest version is not stored again.
+
+#[derive(Debug)]
+pub enum StoreError {
+    Db(sled::Error),
+    Encode(serde_json::Error),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Db(e) => write!(f, "Submission store error: {}", e),
+            StoreError::Encode(e) => write!(f, "Invalid stored submission: {}", e),
+        }
+    }
+}
+
+impl From<sled::Error> for StoreError {
+    fn from(e: sled::Error) -> Self {
+        StoreError::Db(e)
+    }
+}
+
+impl From<serde_json::Error> for StoreError {
+    fn from(e: serde_json::Error) -> Self {
+        StoreError::Encode(e)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StoredVersion {
+    pub version: u32,
+    pub stored_at: DateTime<Utc>,
+    // Where the payload came from, e.g. "download", "dms", "batch"
+    pub source: String,
+    pub submission: Value,
+    // The payload as received, including anything the model drops or
+    // defaults; absent on versions stored before it was kept
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<Value>,
+}
+
+impl StoredVersion {
+    pub fn submission(&self) -> Result<Submission, StoreError> {
+        Ok(serde_json::from_value(self.submission.clone())?)
+    }
+}
+
+// Listing entry; the payload itself is fetched per version.
+#[derive(Serialize, Debug)]
+pub struct VersionSummary {
+    pub version: u32,
+    pub stored_at: DateTime<Utc>,
+    pub source: String,
+    pub status: Option<SubmissionStatus>,
+    pub updated_at: Option<Value>,
+    // `Submission::content_hash` of the payload, as printed in the cover QR code
+    pub content_hash: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct SubmissionStore {
+    versions: sled::Tree,
+    // Serializes writers so concurrent renders can't claim the same version
+    write_lock: Arc<Mutex<()>>,
+}
+
+// Keys are `<unique_id> 0x00 <version as big-endian u32>` so a prefix scan
+// returns one submission's versions in order.
+fn prefix(unique_id: &str) -> Vec<u8> {
+    let mut key = unique_id.as_bytes().to_vec();
+    key.push(0);
+    key
+}
+
+fn key(unique_id: &str, version: u32) -> Vec<u8> {
+    let mut key = prefix(unique_id);
+    key.extend_from_slice(&version.to_be_bytes());
+    key
+}
+
+impl SubmissionStore {
+    pub fn open(path: &str) -> Result<Self, StoreError> {
+        let db = sled::open(path)?;
+        Ok(Self {
+            versions: db.open_tree("versions")?,
+            write_lock: Arc::new(Mutex::new(())),
+        })
+    }
+
+    fn latest(&self, unique_id: &str) -> Result<Option<StoredVersion>, StoreError> {
+        match self.versions.scan_prefix(prefix(unique_id)).next_back() {
+            Some(entry) => {
+                let (_, value) = entry?;
+                Ok(Some(serde_json::from_slice(&value)?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    // Stores the submission, parsed from `raw`, as a new version unless it
+    // matches the latest one. Returns the version the payload is stored under.
+    pub fn record(&self, submission: &Submission, raw: &Value, source: &str) -> Result<u32, StoreError> {
+        let payload = serde_json::to_value(submission)?;
+        let _guard = self.write_lock.lock().unwrap();
+        let latest = self.latest(&submission.unique_id)?;
+        if let Some(latest) = &latest {
+            if latest.submission == payload && latest.raw.as_ref() == Some(raw) {
+                return Ok(latest.version);
+            }
+        }
+
+        let version = latest.map_or(1, |latest| latest.version + 1);
+        let stored = StoredVersion {
+            version,
+            stored_at: Utc::now(),
+            source: source.to_string(),
+            submission: payload,
+            raw: Some(raw.clone()),
+        };
+        self.versions
+            .insert(key(&submission.unique_id, version), serde_json::to_vec(&stored)?)?;
+        self.versions.flush()?;
+        println!("Stored version {} of {}", version, submission.unique_id);
+        Ok(version)
+    }
+
+    pub fn versions(&self, unique_id: &str) -> Result<Vec<VersionSummary>, StoreError> {
+        self.versions
+            .scan_prefix(prefix(unique_id))
+            .map(|entry| {
+                let (_, value) = entry?;
+                let stored: StoredVersion = serde_json::from_slice(&value)?;
+                let content_hash = stored.submission().ok().map(|submission| submission.content_hash());
+                Ok(VersionSummary {
+                    version: stored.version,
+                    stored_at: stored.stored_at,
+                    source: stored.source,
+                    status: stored
+                        .submission
+                        .get("status")
+                        .and_then(|status| serde_json::from_value(status.clone()).ok()),
+                    updated_at: stored.submission.get("updatedAt").cloned(),
+                    content_hash,
+                })
+            })
+            .collect()
+    }
+
+    pub fn version(&self, unique_id: &str, version: u32) -> Result<Option<StoredVersion>, StoreError> {
+        match self.versions.get(key(unique_id, version))? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,257 @@
+use crate::document::{Block, Cell, Document, Paragraph, Section, StyleName, StyleSheet, Table, TextStyle};
+use crate::models::{Submission, TrlLevel};
+use crate::page2::{budget_total, duration_months};
+use crate::render::DocumentFormat;
+use crate::sheet::{Sheet, Value};
+use chrono::{DateTime, Local, Utc};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+// Committee summary: every proposal of a call on one line (unique_id, title,
+// PI, number of Co-PIs, track, duration, TRL and budget) followed by the
+// budget requested per track, for the selection meeting. Rendered as a
+// landscape document or exported as spreadsheet tables. Proposals are
+// grouped by track.
+
+pub struct SummaryRow {
+    pub unique_id: String,
+    pub title: String,
+    pub pi: String,
+    pub co_pis: usize,
+    pub track: String,
+    pub duration_months: i32,
+    pub trl: String,
+    pub budget: i64,
+}
+
+pub struct TrackTotal {
+    pub track: String,
+    pub proposals: usize,
+    pub budget: i64,
+}
+
+pub struct CommitteeSummary {
+    pub rows: Vec<SummaryRow>,
+    pub tracks: Vec<TrackTotal>,
+    // Discarded submissions left out of the summary
+    pub excluded: usize,
+    pub generated_at: DateTime<Local>,
+}
+
+// The track's name without the description the DMS appends, e.g.
+// "Energy & Environment - Enhancing energy efficiency ..."
+fn track_name(track: &str) -> String {
+    track.split(" - ").next().unwrap_or(track).trim().to_string()
+}
+
+fn trl(submission: &Submission) -> String {
+    match &submission.trl_level {
+        Some(TrlLevel::Level(level)) => format!("TRL {}", level),
+        Some(TrlLevel::Unknown(other)) => other.clone(),
+        None => String::new(),
+    }
+}
+
+impl CommitteeSummary {
+    pub fn new(submissions: &[Submission], excluded: usize) -> Self {
+        let mut rows: Vec<SummaryRow> = submissions
+            .iter()
+            .map(|submission| SummaryRow {
+                unique_id: submission.unique_id.clone(),
+                title: submission.project_title.clone().unwrap_or_default(),
+                pi: submission.user.clone(),
+                co_pis: submission.co_pi.as_ref().map_or(0, |co_pis| co_pis.len()),
+                track: track_name(&submission.track),
+                duration_months: duration_months(submission),
+                trl: trl(submission),
+                budget: budget_total(submission),
+            })
+            .collect();
+        rows.sort_by(|a, b| a.track.cmp(&b.track).then_with(|| a.unique_id.cmp(&b.unique_id)));
+
+        let mut tracks: BTreeMap<&str, TrackTotal> = BTreeMap::new();
+        for row in &rows {
+            let total = tracks.entry(&row.track).or_insert_with(|| TrackTotal {
+                track: row.track.clone(),
+                proposals: 0,
+                budget: 0,
+            });
+            total.proposals += 1;
+            total.budget += row.budget;
+        }
+        let tracks = tracks.into_values().collect();
+
+        Self {
+            rows,
+            tracks,
+            excluded,
+            generated_at: Local::now(),
+        }
+    }
+
+    pub fn total_budget(&self) -> i64 {
+        self.tracks.iter().map(|track| track.budget).sum()
+    }
+}
+
+// `?format=` of the summary: a document, or its tables as a spreadsheet
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum SummaryFormat {
+    Document(DocumentFormat),
+    Csv,
+    Xlsx,
+}
+
+impl Default for SummaryFormat {
+    fn default() -> Self {
+        SummaryFormat::Document(DocumentFormat::Pdf)
+    }
+}
+
+impl TryFrom<String> for SummaryFormat {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.to_ascii_lowercase().as_str() {
+            "csv" => Ok(SummaryFormat::Csv),
+            "xlsx" => Ok(SummaryFormat::Xlsx),
+            _ => DocumentFormat::try_from(value.clone())
+                .map(SummaryFormat::Document)
+                .map_err(|_| format!("Unsupported format \"{}\"; expected pdf, docx, html, csv or xlsx", value)),
+        }
+    }
+}
+
+pub fn summary_filename(extension: &str) -> String {
+    format!("committee_summary_{}.{}", Utc::now().format("%Y%m%d_%H%M%S"), extension)
+}
+
+fn proposals_table(summary: &CommitteeSummary) -> Table {
+    let mut rows = vec![vec![
+        Cell::bold("#").width(500),
+        Cell::bold("Unique ID").width(1900),
+        Cell::bold("Project Title").width(4000),
+        Cell::bold("PI").width(2400),
+        Cell::bold("Co-PIs").width(800),
+        Cell::bold("Track").width(1900),
+        Cell::bold("Duration (months)").width(1000),
+        Cell::bold("TRL").width(700),
+        Cell::bold("Total Budget").width(1300),
+    ]];
+    for (index, row) in summary.rows.iter().enumerate() {
+        rows.push(vec![
+            Cell::new((index + 1).to_string()),
+            Cell::new(&row.unique_id),
+            Cell::new(&row.title),
+            Cell::new(&row.pi),
+            Cell::new(row.co_pis.to_string()),
+            Cell::new(&row.track),
+            Cell::new(row.duration_months.to_string()),
+            Cell::new(&row.trl),
+            Cell::new(row.budget.to_string()),
+        ]);
+    }
+    Table { rows }
+}
+
+fn tracks_table(summary: &CommitteeSummary) -> Table {
+    let mut rows = vec![vec![
+        Cell::bold("Track").width(6000),
+        Cell::bold("Proposals").width(2000),
+        Cell::bold("Total Budget").width(3000),
+    ]];
+    for track in &summary.tracks {
+        rows.push(vec![
+            Cell::new(&track.track),
+            Cell::new(track.proposals.to_string()),
+            Cell::new(track.budget.to_string()),
+        ]);
+    }
+    rows.push(vec![
+        Cell::bold("Total"),
+        Cell::bold(&summary.rows.len().to_string()),
+        Cell::bold(&summary.total_budget().to_string()),
+    ]);
+    Table { rows }
+}
+
+pub fn summary_document(summary: &CommitteeSummary, styles: &StyleSheet) -> Document {
+    let mut blocks = vec![
+        Block::Paragraph(
+            Paragraph::styled(StyleName::CoverText).text("COE-DSAI Seed Grant Proposals", TextStyle::new()),
+        ),
+        Block::Paragraph(Paragraph::centered().text(
+            format!(
+                "{} proposal(s), generated on {}",
+                summary.rows.len(),
+                summary.generated_at.format("%d %B %Y, %H:%M")
+            ),
+            TextStyle::new(),
+        )),
+        Block::Spacer,
+        Block::Heading("Proposals".to_string()),
+        Block::Table(proposals_table(summary)),
+    ];
+    if summary.excluded > 0 {
+        blocks.push(Block::Text(format!(
+            "{} discarded proposal(s) not listed.",
+            summary.excluded
+        )));
+    }
+    blocks.push(Block::Spacer);
+    blocks.push(Block::Heading("Budget by Track".to_string()));
+    blocks.push(Block::Table(tracks_table(summary)));
+
+    Document {
+        title: "Committee Summary".to_string(),
+        styles: styles.clone(),
+        sections: vec![Section::titled("Committee Summary", blocks)],
+        landscape: true,
+    }
+}
+
+// "Proposals" and "Tracks" tables for CSV and XLSX
+pub fn summary_sheets(summary: &CommitteeSummary) -> Vec<Sheet> {
+    let mut proposals = Sheet::new(
+        "Proposals",
+        &[
+            "Unique ID",
+            "Project Title",
+            "PI",
+            "Co-PIs",
+            "Track",
+            "Duration (months)",
+            "TRL",
+            "Total Budget",
+        ],
+    );
+    for row in &summary.rows {
+        proposals.rows.push(vec![
+            Value::from(row.unique_id.as_str()),
+            Value::from(row.title.as_str()),
+            Value::from(row.pi.as_str()),
+            Value::from(row.co_pis as i64),
+            Value::from(row.track.as_str()),
+            Value::from(row.duration_months as i64),
+            Value::from(row.trl.as_str()),
+            Value::from(row.budget),
+        ]);
+    }
+
+    let mut tracks = Sheet::new("Tracks", &["Track", "Proposals", "Total Budget"]);
+    for track in &summary.tracks {
+        tracks.rows.push(vec![
+            Value::from(track.track.as_str()),
+            Value::from(track.proposals as i64),
+            Value::from(track.budget),
+        ]);
+    }
+    tracks.rows.push(vec![
+        Value::from("Total"),
+        Value::from(summary.rows.len() as i64),
+        Value::from(summary.total_budget()),
+    ]);
+
+    vec![proposals, tracks]
+}
@@ -0,0 +1,116 @@
+use image::ImageFormat;
+use serde::Deserialize;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Cover page thumbnails. pdftoppm (poppler-utils) rasterizes page 1 of the
+// rendered PDF to PNG; WebP is re-encoded from that PNG.
+
+pub const DEFAULT_WIDTH: u32 = 300;
+pub const MIN_WIDTH: u32 = 32;
+pub const MAX_WIDTH: u32 = 2000;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThumbnailFormat {
+    #[default]
+    Png,
+    Webp,
+}
+
+impl ThumbnailFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ThumbnailFormat::Png => "png",
+            ThumbnailFormat::Webp => "webp",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ThumbnailFormat::Png => "image/png",
+            ThumbnailFormat::Webp => "image/webp",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ThumbnailError {
+    TempFile(io::Error),
+    Rasterize(io::Error),
+    Encode(String),
+}
+
+impl ThumbnailError {
+    // Short message safe to return to API clients
+    pub fn message(&self) -> &'static str {
+        match self {
+            ThumbnailError::TempFile(_) => "Failed to write temporary file",
+            ThumbnailError::Rasterize(_) => "Failed to rasterize cover page",
+            ThumbnailError::Encode(_) => "Failed to encode thumbnail",
+        }
+    }
+}
+
+impl fmt::Display for ThumbnailError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThumbnailError::TempFile(e) | ThumbnailError::Rasterize(e) => {
+                write!(f, "{}: {}", self.message(), e)
+            }
+            ThumbnailError::Encode(e) => write!(f, "{}: {}", self.message(), e),
+        }
+    }
+}
+
+// Keeps temporary files of concurrent rasterizations apart
+static THUMBNAIL_SEQ: AtomicUsize = AtomicUsize::new(0);
+
+// Rasterizes the first page of `pdf` at `width` pixels (height follows the
+// page's aspect ratio).
+pub fn cover_thumbnail(pdf: &[u8], width: u32, format: ThumbnailFormat) -> Result<Vec<u8>, ThumbnailError> {
+    let seq = THUMBNAIL_SEQ.fetch_add(1, Ordering::Relaxed);
+    let prefix = std::env::temp_dir().join(format!("dms-pdfmaker-thumb-{}-{}", std::process::id(), seq));
+    let pdf_path = prefix.with_extension("pdf");
+    // pdftoppm appends the extension to the output prefix
+    let png_path = prefix.with_extension("png");
+
+    fs::write(&pdf_path, pdf).map_err(ThumbnailError::TempFile)?;
+    let width_arg = width.to_string();
+    let output = Command::new("pdftoppm")
+        .args(["-png", "-f", "1", "-l", "1", "-singlefile", "-scale-to-x", &width_arg, "-scale-to-y", "-1"])
+        .arg(&pdf_path)
+        .arg(&prefix)
+        .output();
+    let _ = fs::remove_file(&pdf_path);
+
+    let output = output.map_err(ThumbnailError::Rasterize)?;
+    if !output.status.success() {
+        let _ = fs::remove_file(&png_path);
+        println!("pdftoppm stderr: {}", String::from_utf8_lossy(&output.stderr));
+        return Err(ThumbnailError::Rasterize(io::Error::other(format!(
+            "pdftoppm exited with {}",
+            output.status
+        ))));
+    }
+
+    let png = fs::read(&png_path).map_err(ThumbnailError::Rasterize);
+    let _ = fs::remove_file(&png_path);
+    let png = png?;
+
+    match format {
+        ThumbnailFormat::Png => Ok(png),
+        ThumbnailFormat::Webp => {
+            let image = image::load_from_memory_with_format(&png, ImageFormat::Png)
+                .map_err(|e| ThumbnailError::Encode(e.to_string()))?;
+            let mut webp = io::Cursor::new(Vec::new());
+            image
+                .write_to(&mut webp, ImageFormat::WebP)
+                .map_err(|e| ThumbnailError::Encode(e.to_string()))?;
+            Ok(webp.into_inner())
+        }
+    }
+}
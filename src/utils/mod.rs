@@ -1,31 +1,26 @@
+use crate::fonts;
 use std::process::Command;
 use std::fs;
 use std::path::Path;
 
-use docx_rs::{Paragraph, Run, TableCell, RunFonts};
+// LibreOffice export filters. Fonts are embedded in both, including the
+// standard 14 PDF fonts that viewers would otherwise substitute; PDF/A-2b
+// also writes XMP metadata.
+const PDF_EXPORT: &str = r#"pdf:writer_pdf_Export:{"EmbedStandardFonts":{"type":"boolean","value":"true"}}"#;
+const PDFA_EXPORT: &str = r#"pdf:writer_pdf_Export:{"SelectPdfVersion":{"type":"long","value":"2"},"EmbedStandardFonts":{"type":"boolean","value":"true"}}"#;
 
-pub fn create_paragraph(text: &str) -> Paragraph {
-    Paragraph::new().add_run(
-        Run::new()
-            .add_text(text)
-            .size(28)
-            .fonts(RunFonts::new().ascii("Calibri"))  // Using Calibri for table content
-            .color("#333333")  // Dark gray for better readability
-    )
-}
-pub fn empty_row(cols: usize) -> Vec<TableCell> {
-    (0..cols)
-        .map(|_| TableCell::new().add_paragraph(Paragraph::new()))
-        .collect()
-}
-
-#[allow(dead_code)]
-pub fn convert_docx_to_pdf(input: &str, output_dir: &str, output_filename: Option<&str>) -> std::io::Result<String> {
+pub fn convert_docx_to_pdf(
+    input: &str,
+    output_dir: &str,
+    output_filename: Option<&str>,
+    pdfa: bool,
+) -> std::io::Result<String> {
     // Print debug information
     println!("Starting PDF conversion");
     println!("Input file: {}", input);
     println!("Output directory: {}", output_dir);
     println!("Output filename: {:?}", output_filename);
+    println!("PDF/A: {}", pdfa);
     
     // Create the output directory if it doesn't exist
     fs::create_dir_all(output_dir)?;
@@ -37,18 +32,28 @@ pub fn convert_docx_to_pdf(input: &str, output_dir: &str, output_filename: Optio
         return Err(std::io::Error::new(std::io::ErrorKind::NotFound, "Input file not found"));
     }
     
+    // Each conversion gets its own LibreOffice profile; instances sharing the
+    // default profile refuse to run concurrently
+    let input_path = Path::new(input);
+    let input_stem = input_path.file_stem().unwrap().to_str().unwrap();
+    let profile_dir = std::env::temp_dir().join(format!("dms-pdfmaker-lo-{}", input_stem));
+    let user_installation = format!("-env:UserInstallation=file://{}", profile_dir.display());
+
     // Run LibreOffice to convert the DOCX to PDF with more verbose output
     println!("Executing LibreOffice command...");
-    let output = Command::new("libreoffice")
+    let output = fonts::configure(&mut Command::new("libreoffice"))
         .args([
+            user_installation.as_str(),
             "--headless",
             "--convert-to",
-            "pdf",
+            if pdfa { PDFA_EXPORT } else { PDF_EXPORT },
             "--outdir",
             output_dir,
             input,
         ])
-        .output()?;
+        .output();
+    let _ = fs::remove_dir_all(&profile_dir);
+    let output = output?;
     
     println!("LibreOffice stdout: {}", String::from_utf8_lossy(&output.stdout));
     println!("LibreOffice stderr: {}", String::from_utf8_lossy(&output.stderr));
@@ -56,13 +61,9 @@ pub fn convert_docx_to_pdf(input: &str, output_dir: &str, output_filename: Optio
     let status = output.status;
     if !status.success() {
         eprintln!("Conversion failed with status: {}", status);
-        return Err(std::io::Error::new(std::io::ErrorKind::Other, "LibreOffice conversion failed"));
+        return Err(std::io::Error::other("LibreOffice conversion failed"));
     }
 
-    // Get the base filename without extension
-    let input_path = Path::new(input);
-    let input_stem = input_path.file_stem().unwrap().to_str().unwrap();
-    
     // Calculate the default output path from LibreOffice
     let default_output = format!("{}/{}.pdf", output_dir, input_stem);
     println!("Default output path: {}", default_output);
@@ -73,10 +74,8 @@ pub fn convert_docx_to_pdf(input: &str, output_dir: &str, output_filename: Optio
         // List files in the output directory
         println!("Files in output directory:");
         if let Ok(entries) = fs::read_dir(output_dir) {
-            for entry in entries {
-                if let Ok(entry) = entry {
-                    println!("  {}", entry.path().display());
-                }
+            for entry in entries.flatten() {
+                println!("  {}", entry.path().display());
             }
         }
         return Err(std::io::Error::new(std::io::ErrorKind::NotFound, "Output PDF not created"));
@@ -94,3 +93,14 @@ pub fn convert_docx_to_pdf(input: &str, output_dir: &str, output_filename: Optio
         Ok(default_output)
     }
 }
+
+// Percent-encodes everything outside RFC 3986's unreserved characters, for
+// a URL path segment or query value
+pub fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}